@@ -0,0 +1,29 @@
+#![no_main]
+
+use key_path::{Item, KeyPath};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(s) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    // Parsed paths never hold empty keys, so the escaped form reads back
+    // exactly. `Display` doesn't escape keys made of digits.
+    if let Ok(path) = s.parse::<KeyPath>() {
+        let rendered = path.to_escaped_string();
+        let reparsed = rendered.parse::<KeyPath>().expect("escaped output must parse");
+        assert_eq!(reparsed, path, "{:?} rendered as {:?}", s, rendered);
+
+        // Appending `Item::NextIndex` and the key `-` must survive as well.
+        let extended = path + Item::NextIndex + "-";
+        let rendered = extended.to_escaped_string();
+        assert_eq!(rendered.parse::<KeyPath>().as_ref(), Ok(&extended), "{:?} rendered as {:?}", s, rendered);
+    }
+
+    if let Ok(path) = KeyPath::from_json_pointer(s) {
+        let rendered = path.to_json_pointer();
+        assert_eq!(rendered, s, "pointer {:?} rendered as {:?}", s, rendered);
+        assert_eq!(KeyPath::from_json_pointer(&rendered).as_ref(), Ok(&path));
+    }
+});
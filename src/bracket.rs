@@ -0,0 +1,267 @@
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use crate::{Item, KeyPath, KeyPathParseError, KeyPathParseErrorKind::*};
+use crate::parse::unexpected_char;
+
+impl KeyPath {
+
+    /// Parses JavaScript style paths like `foo.bar[2][5].baz`.
+    ///
+    /// Dotted segments are always keys and bracketed segments are indices,
+    /// unless the brackets hold a double quoted key like `["a.b"]`, inside
+    /// which `\"` and `\\` are the only escapes. `[-]` is `Item::NextIndex`.
+    pub fn parse_bracket(s: &str) -> Result<Self, KeyPathParseError> {
+        let mut items = vec![];
+        let mut pos = 0;
+        while pos < s.len() {
+            let rest = &s[pos..];
+            if let Some(inner) = rest.strip_prefix('[') {
+                if inner.starts_with('"') {
+                    let (key, len) = parse_quoted_key(inner, pos + 1)?;
+                    if !inner[len..].starts_with(']') {
+                        return Err(KeyPathParseError::new(UnclosedBracket, pos, rest));
+                    }
+                    items.push(Item::Key(key.into()));
+                    pos += len + 2;
+                } else {
+                    let close = inner.find(']').ok_or_else(|| KeyPathParseError::new(UnclosedBracket, pos, rest))?;
+                    items.push(parse_bracket_index(&inner[..close], pos + 1)?);
+                    pos += close + 2;
+                }
+                continue;
+            }
+            if pos > 0 {
+                match rest.strip_prefix('.') {
+                    Some(_) => pos += 1,
+                    None => return Err(unexpected_char(s, pos)),
+                }
+            } else if rest.starts_with('.') {
+                return Err(unexpected_char(s, pos));
+            }
+            let rest = &s[pos..];
+            let end = rest.find(['.', '[', ']']).unwrap_or(rest.len());
+            if end == 0 {
+                return Err(if rest.starts_with(']') {
+                    unexpected_char(s, pos)
+                } else {
+                    KeyPathParseError::new(EmptySegment, pos, "")
+                });
+            }
+            items.push(Item::Key(rest[..end].to_owned().into()));
+            pos += end;
+        }
+        Ok(KeyPath::new(items))
+    }
+
+    /// Renders the path with indices in brackets, e.g. `where.items[5].name`.
+    pub fn to_bracket_string(&self) -> String {
+        let mut result = String::new();
+        for (i, item) in self.iter().enumerate() {
+            match item {
+                Item::Key(key) => {
+                    if i > 0 {
+                        result.push('.');
+                    }
+                    result.push_str(key);
+                }
+                Item::Index(index) => {
+                    result.push('[');
+                    result.push_str(&index.to_string());
+                    result.push(']');
+                }
+                Item::NextIndex => result.push_str("[-]"),
+            }
+        }
+        result
+    }
+
+    /// Renders the path like `to_bracket_string`, except that keys which are
+    /// empty or contain `.`, `[`, `]`, `"`, `\\` or whitespace are written as
+    /// quoted brackets: `data["weird.key[]"]`. `parse_bracket` reads it back.
+    pub fn to_quoted_string(&self) -> String {
+        let mut result = String::new();
+        for (i, item) in self.iter().enumerate() {
+            match item {
+                Item::Key(key) if is_plain_key(key) => {
+                    if i > 0 {
+                        result.push('.');
+                    }
+                    result.push_str(key);
+                }
+                Item::Key(key) => {
+                    result.push_str("[\"");
+                    for c in key.chars() {
+                        if c == '"' || c == '\\' {
+                            result.push('\\');
+                        }
+                        result.push(c);
+                    }
+                    result.push_str("\"]");
+                }
+                Item::Index(index) => {
+                    result.push('[');
+                    result.push_str(&index.to_string());
+                    result.push(']');
+                }
+                Item::NextIndex => result.push_str("[-]"),
+            }
+        }
+        result
+    }
+}
+
+fn is_plain_key(key: &str) -> bool {
+    !key.is_empty() && !key.chars().any(|c| matches!(c, '.' | '[' | ']' | '"' | '\\') || c.is_whitespace())
+}
+
+fn parse_quoted_key(s: &str, offset: usize) -> Result<(String, usize), KeyPathParseError> {
+    let mut key = String::new();
+    let mut chars = s.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((key, i + 1)),
+            '\\' => match chars.next() {
+                Some((_, c @ ('"' | '\\'))) => key.push(c),
+                _ => return Err(KeyPathParseError::new(InvalidEscape, offset + i, s[i..].chars().take(2).collect::<String>())),
+            },
+            c => key.push(c),
+        }
+    }
+    Err(KeyPathParseError::new(UnclosedQuote, offset, s))
+}
+
+fn parse_bracket_index(segment: &str, offset: usize) -> Result<Item, KeyPathParseError> {
+    if segment == "-" {
+        return Ok(Item::NextIndex);
+    }
+    if segment.is_empty() || !segment.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(KeyPathParseError::new(InvalidIndex, offset, segment));
+    }
+    segment.parse().map(Item::Index).map_err(|_| KeyPathParseError::new(IndexOverflow, offset, segment))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path;
+
+    #[test]
+    fn parse_bracket_works() {
+        let result = KeyPath::parse_bracket("foo.bar[2][5].baz").unwrap();
+        assert_eq!(result, path!["foo", "bar", 2, 5, "baz"]);
+    }
+
+    #[test]
+    fn parse_bracket_works_for_leading_index() {
+        let result = KeyPath::parse_bracket("[0].name").unwrap();
+        assert_eq!(result, path![0, "name"]);
+    }
+
+    #[test]
+    fn parse_bracket_keeps_dotted_digits_as_keys() {
+        let result = KeyPath::parse_bracket("a.0").unwrap();
+        assert_eq!(result, path!["a", "0"]);
+    }
+
+    #[test]
+    fn parse_bracket_works_for_empty() {
+        assert_eq!(KeyPath::parse_bracket("").unwrap(), KeyPath::default());
+    }
+
+    #[test]
+    fn to_bracket_string_works() {
+        let path = path!["where", "items", 5, "name"];
+        assert_eq!(&path.to_bracket_string(), "where.items[5].name");
+    }
+
+    #[test]
+    fn to_bracket_string_works_for_empty() {
+        assert_eq!(&KeyPath::default().to_bracket_string(), "");
+    }
+
+    #[test]
+    fn to_bracket_string_works_for_leading_index() {
+        assert_eq!(&path![5, "name"].to_bracket_string(), "[5].name");
+    }
+
+    #[test]
+    fn to_bracket_string_works_for_indices_only() {
+        assert_eq!(&path![1, 2].to_bracket_string(), "[1][2]");
+        assert_eq!(&path!["a", 1, 2].to_bracket_string(), "a[1][2]");
+    }
+
+    #[test]
+    fn bracket_string_round_trips() {
+        for path in [path!["user", "tags", 2, "name"], path![0, 1, "a"], path!["a"], KeyPath::default()] {
+            assert_eq!(KeyPath::parse_bracket(&path.to_bracket_string()).unwrap(), path);
+        }
+        assert_eq!(&KeyPath::parse_bracket("a[0][1]").unwrap().to_bracket_string(), "a[0][1]");
+    }
+
+    #[test]
+    fn to_quoted_string_works() {
+        let path = path!["data", "weird.key[]", 0, "name"];
+        assert_eq!(&path.to_quoted_string(), r#"data["weird.key[]"][0].name"#);
+    }
+
+    #[test]
+    fn to_quoted_string_escapes_quotes_and_backslashes() {
+        let path = path![r#"say "hi""#, r"back\slash", ""];
+        assert_eq!(&path.to_quoted_string(), r#"["say \"hi\""]["back\\slash"][""]"#);
+    }
+
+    #[test]
+    fn parse_bracket_works_for_quoted_keys() {
+        let result = KeyPath::parse_bracket(r#"data["weird.key[]"]["a \"b\" \\"].x"#).unwrap();
+        assert_eq!(result, path!["data", "weird.key[]", r#"a "b" \"#, "x"]);
+    }
+
+    #[test]
+    fn quoted_string_round_trips() {
+        let path = path!["data", "", "a b", 3, "x.y", r#"q"\"#, "[]", "0"] + Item::NextIndex;
+        assert_eq!(KeyPath::parse_bracket(&path.to_quoted_string()).unwrap(), path);
+    }
+
+    #[test]
+    fn parse_bracket_errors_on_unclosed_quote() {
+        assert_eq!(KeyPath::parse_bracket(r#"a["b"#), Err(KeyPathParseError::new(UnclosedQuote, 2, r#""b"#)));
+        assert_eq!(KeyPath::parse_bracket(r#"a["b""#), Err(KeyPathParseError::new(UnclosedBracket, 1, r#"["b""#)));
+        assert_eq!(KeyPath::parse_bracket(r#"a["\n"]"#), Err(KeyPathParseError::new(InvalidEscape, 3, r"\n")));
+    }
+
+    #[test]
+    fn parse_bracket_errors_on_unclosed_bracket() {
+        let result = KeyPath::parse_bracket("a[2");
+        assert_eq!(result, Err(KeyPathParseError::new(UnclosedBracket, 1, "[2")));
+    }
+
+    #[test]
+    fn parse_bracket_errors_on_non_numeric_index() {
+        let result = KeyPath::parse_bracket("a[b]");
+        assert_eq!(result, Err(KeyPathParseError::new(InvalidIndex, 2, "b")));
+        let result = KeyPath::parse_bracket("a[abc]");
+        assert_eq!(result, Err(KeyPathParseError::new(InvalidIndex, 2, "abc")));
+        let result = KeyPath::parse_bracket("a[]");
+        assert_eq!(result, Err(KeyPathParseError::new(InvalidIndex, 2, "")));
+    }
+
+    #[test]
+    fn parse_bracket_errors_on_leading_dot() {
+        let result = KeyPath::parse_bracket(".a");
+        assert_eq!(result, Err(KeyPathParseError::new(UnexpectedChar, 0, ".")));
+        assert_eq!(result.unwrap_err().to_string(), "unexpected `.` at offset 0");
+    }
+
+    #[test]
+    fn parse_bracket_errors_on_empty_segment() {
+        assert_eq!(KeyPath::parse_bracket("a..b"), Err(KeyPathParseError::new(EmptySegment, 2, "")));
+        assert_eq!(KeyPath::parse_bracket("a."), Err(KeyPathParseError::new(EmptySegment, 2, "")));
+    }
+
+    #[test]
+    fn parse_bracket_errors_on_stray_characters() {
+        assert_eq!(KeyPath::parse_bracket("a[0]b"), Err(KeyPathParseError::new(UnexpectedChar, 4, "b")));
+        assert_eq!(KeyPath::parse_bracket("a]"), Err(KeyPathParseError::new(UnexpectedChar, 1, "]")));
+    }
+}
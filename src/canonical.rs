@@ -0,0 +1,166 @@
+use alloc::string::{String, ToString};
+use alloc::vec;
+use crate::{Item, KeyPath, KeyPathParseError, KeyPathParseErrorKind::*};
+use crate::parse::unexpected_char;
+
+impl KeyPath {
+
+    /// Renders the path in a form that differs for every distinct path, for
+    /// use as a storage or map key.
+    ///
+    /// Each key is written as `.` followed by the key with `\`, `.` and `#`
+    /// backslash-escaped, each index as `#` followed by its decimal digits,
+    /// and `Item::NextIndex` as `#-`. So `path!["a", 1]` is `.a#1` while
+    /// `path!["a", "1"]` is `.a.1`, and the empty path is the empty string.
+    pub fn to_canonical_string(&self) -> String {
+        let mut result = String::new();
+        for item in self {
+            match item {
+                Item::Key(key) => {
+                    result.push('.');
+                    for c in key.chars() {
+                        if matches!(c, '\\' | '.' | '#') {
+                            result.push('\\');
+                        }
+                        result.push(c);
+                    }
+                }
+                Item::Index(index) => {
+                    result.push('#');
+                    result.push_str(&index.to_string());
+                }
+                Item::NextIndex => result.push_str("#-"),
+            }
+        }
+        result
+    }
+
+    /// Parses the form produced by `to_canonical_string`.
+    ///
+    /// Only strings that `to_canonical_string` can produce are accepted:
+    /// escapes other than `\\`, `\.` and `\#` and indices with leading zeros
+    /// are errors, so the two functions are inverses of each other.
+    pub fn from_canonical_string(s: &str) -> Result<Self, KeyPathParseError> {
+        let mut items = vec![];
+        let mut chars = s.char_indices().peekable();
+        while let Some((offset, tag)) = chars.next() {
+            match tag {
+                '.' => {
+                    let mut key = String::new();
+                    while let Some(&(i, c)) = chars.peek() {
+                        match c {
+                            '.' | '#' => break,
+                            '\\' => {
+                                chars.next();
+                                match chars.next() {
+                                    Some((_, c)) if matches!(c, '\\' | '.' | '#') => key.push(c),
+                                    Some((j, c)) => return Err(KeyPathParseError::new(InvalidEscape, i, &s[i..j + c.len_utf8()])),
+                                    None => return Err(KeyPathParseError::new(InvalidEscape, i, "\\")),
+                                }
+                            }
+                            c => {
+                                chars.next();
+                                key.push(c);
+                            }
+                        }
+                    }
+                    items.push(Item::Key(key.into()));
+                }
+                '#' => {
+                    let start = offset + 1;
+                    let end = s[start..].find(['.', '#']).map_or(s.len(), |len| start + len);
+                    let segment = &s[start..end];
+                    if segment == "-" {
+                        items.push(Item::NextIndex);
+                    } else if segment.is_empty() || !segment.bytes().all(|b| b.is_ascii_digit()) || (segment.len() > 1 && segment.starts_with('0')) {
+                        return Err(KeyPathParseError::new(InvalidIndex, start, segment));
+                    } else {
+                        items.push(Item::Index(segment.parse().map_err(|_| KeyPathParseError::new(IndexOverflow, start, segment))?));
+                    }
+                    while chars.peek().is_some_and(|&(i, _)| i < end) {
+                        chars.next();
+                    }
+                }
+                _ => return Err(unexpected_char(s, offset)),
+            }
+        }
+        Ok(KeyPath::new(items))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path;
+
+    #[test]
+    fn to_canonical_string_works() {
+        assert_eq!(path!["a", 1].to_canonical_string(), ".a#1");
+        assert_eq!(path!["a", "1"].to_canonical_string(), ".a.1");
+        assert_eq!(path!["a.b", r"c\d", "#e"].to_canonical_string(), r".a\.b.c\\d.\#e");
+        assert_eq!(path!["", 0].to_canonical_string(), ".#0");
+        assert_eq!(KeyPath::new(vec![Item::NextIndex, Item::Key("-".into())]).to_canonical_string(), "#-.-");
+        assert_eq!(KeyPath::default().to_canonical_string(), "");
+    }
+
+    #[test]
+    fn to_canonical_string_tells_apart_colliding_displays() {
+        assert_eq!(path!["a", 1].to_string(), path!["a", "1"].to_string());
+        assert_ne!(path!["a", 1].to_canonical_string(), path!["a", "1"].to_canonical_string());
+        assert_ne!(path!["a.b"].to_canonical_string(), path!["a", "b"].to_canonical_string());
+        assert_ne!(path![""].to_canonical_string(), KeyPath::default().to_canonical_string());
+    }
+
+    #[test]
+    fn from_canonical_string_works() {
+        assert_eq!(KeyPath::from_canonical_string(".a#1"), Ok(path!["a", 1]));
+        assert_eq!(KeyPath::from_canonical_string(r".a\.b.c\\d.\#e"), Ok(path!["a.b", r"c\d", "#e"]));
+        assert_eq!(KeyPath::from_canonical_string("."), Ok(path![""]));
+        assert_eq!(KeyPath::from_canonical_string(""), Ok(KeyPath::default()));
+    }
+
+    #[test]
+    fn from_canonical_string_rejects_non_canonical_input() {
+        assert_eq!(KeyPath::from_canonical_string("a"), Err(KeyPathParseError::new(UnexpectedChar, 0, "a")));
+        assert_eq!(KeyPath::from_canonical_string(r".a\b"), Err(KeyPathParseError::new(InvalidEscape, 2, r"\b")));
+        assert_eq!(KeyPath::from_canonical_string(r".a\"), Err(KeyPathParseError::new(InvalidEscape, 2, "\\")));
+        assert_eq!(KeyPath::from_canonical_string("#01"), Err(KeyPathParseError::new(InvalidIndex, 1, "01")));
+        assert_eq!(KeyPath::from_canonical_string("#"), Err(KeyPathParseError::new(InvalidIndex, 1, "")));
+        assert_eq!(KeyPath::from_canonical_string("#1a"), Err(KeyPathParseError::new(InvalidIndex, 1, "1a")));
+        assert_eq!(KeyPath::from_canonical_string("#99999999999999999999999"), Err(KeyPathParseError::new(IndexOverflow, 1, "99999999999999999999999")));
+    }
+
+    /// A small xorshift generator, so the round trip test covers many paths
+    /// without a dependency.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self, bound: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % bound as u64) as usize
+        }
+    }
+
+    fn random_path(rng: &mut Rng) -> KeyPath {
+        const CHARS: &[char] = &['a', 'Z', '0', '9', '.', '#', '\\', '-', ' ', 'é', '日'];
+        (0..rng.next(6)).map(|_| match rng.next(4) {
+            0 => Item::Index(rng.next(1000)),
+            1 => Item::NextIndex,
+            _ => Item::Key((0..rng.next(5)).map(|_| CHARS[rng.next(CHARS.len())]).collect()),
+        }).collect()
+    }
+
+    #[test]
+    fn canonical_string_round_trips_for_random_paths() {
+        let mut rng = Rng(0x2545f4914f6cdd1d);
+        for _ in 0..10_000 {
+            let path = random_path(&mut rng);
+            let canonical = path.to_canonical_string();
+            assert_eq!(KeyPath::from_canonical_string(&canonical), Ok(path.clone()), "{}", canonical);
+        }
+        let max = path![usize::MAX];
+        assert_eq!(KeyPath::from_canonical_string(&max.to_canonical_string()), Ok(max));
+    }
+}
@@ -0,0 +1,71 @@
+use alloc::string::String;
+use crate::{write_escaped_path, KeyPath};
+
+impl KeyPath {
+
+    /// Renders the path on one line and underlines the item at `index` on the
+    /// next, for pointing at the part of a path that failed:
+    ///
+    /// ```text
+    /// user.addresses.2.zip
+    ///      ^~~~~~~~~
+    /// ```
+    ///
+    /// An `index` past the end saturates to the last item. The empty path
+    /// renders as an empty line followed by a lone `^`. Columns are counted in
+    /// `char`s of the escaped `Display` output.
+    pub fn render_pointing_at(&self, index: usize) -> String {
+        let index = index.min(self.len().saturating_sub(1));
+        let mut line = String::new();
+        let (mut start, mut width) = (0, 0);
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                line.push('.');
+            }
+            let before = line.chars().count();
+            write_escaped_path(&mut line, core::slice::from_ref(item)).expect("writing to a String can't fail");
+            if i == index {
+                start = before;
+                width = line.chars().count() - before;
+            }
+        }
+        line.push('\n');
+        line.extend(core::iter::repeat_n(' ', start));
+        line.push('^');
+        line.extend(core::iter::repeat_n('~', width.saturating_sub(1)));
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{path, KeyPath};
+
+    #[test]
+    fn render_pointing_at_works() {
+        let path = path!["user", "addresses", 2, "zip"];
+        assert_eq!(path.render_pointing_at(0), "user.addresses.2.zip\n^~~~");
+        assert_eq!(path.render_pointing_at(1), "user.addresses.2.zip\n     ^~~~~~~~~");
+        assert_eq!(path.render_pointing_at(2), "user.addresses.2.zip\n               ^");
+        assert_eq!(path.render_pointing_at(3), "user.addresses.2.zip\n                 ^~~");
+    }
+
+    #[test]
+    fn render_pointing_at_accounts_for_escapes() {
+        let path = path!["a.b", "c"];
+        assert_eq!(path.render_pointing_at(0), "a\\.b.c\n^~~~");
+        assert_eq!(path.render_pointing_at(1), "a\\.b.c\n     ^");
+    }
+
+    #[test]
+    fn render_pointing_at_saturates_out_of_range_indices() {
+        let path = path!["a", "bc"];
+        assert_eq!(path.render_pointing_at(9), path.render_pointing_at(1));
+        assert_eq!(KeyPath::default().render_pointing_at(0), "\n^");
+    }
+
+    #[test]
+    fn render_pointing_at_works_for_empty_keys() {
+        assert_eq!(path!["a", "", "b"].render_pointing_at(1), "a..b\n  ^");
+    }
+}
@@ -0,0 +1,105 @@
+use alloc::string::{String, ToString};
+use crate::{Item, KeyPath};
+
+/// Key case conversions following the rules of serde's `rename_all`, so that
+/// converted paths line up with serde field names. Indices are left as they
+/// are.
+///
+/// `to_camel_case` and `to_pascal_case` convert like serde does for snake_case
+/// field names: each `_` is dropped and the letter after it uppercased.
+/// `to_snake_case` and `to_kebab_case` convert like serde does for PascalCase
+/// variant names: a separator goes before every uppercase letter but the
+/// first, so acronyms are split letter by letter and `HTTPCode` becomes
+/// `h_t_t_p_code`.
+impl KeyPath {
+
+    pub fn to_camel_case(&self) -> KeyPath {
+        self.map_keys(|key| {
+            let pascal = pascal_case(key);
+            match pascal.chars().next() {
+                Some(first) => first.to_ascii_lowercase().to_string() + &pascal[first.len_utf8()..],
+                None => pascal,
+            }
+        })
+    }
+
+    pub fn to_pascal_case(&self) -> KeyPath {
+        self.map_keys(pascal_case)
+    }
+
+    pub fn to_snake_case(&self) -> KeyPath {
+        self.map_keys(snake_case)
+    }
+
+    pub fn to_kebab_case(&self) -> KeyPath {
+        self.map_keys(|key| snake_case(key).replace('_', "-"))
+    }
+
+    fn map_keys(&self, mut f: impl FnMut(&str) -> String) -> KeyPath {
+        self.map(|item| match item {
+            Item::Key(key) => Item::Key(f(key).into()),
+            other => other.clone(),
+        })
+    }
+}
+
+fn pascal_case(key: &str) -> String {
+    let mut result = String::with_capacity(key.len());
+    let mut capitalize = true;
+    for c in key.chars() {
+        if c == '_' {
+            capitalize = true;
+        } else if capitalize {
+            result.push(c.to_ascii_uppercase());
+            capitalize = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+fn snake_case(key: &str) -> String {
+    let mut result = String::with_capacity(key.len());
+    for (i, c) in key.char_indices() {
+        if i > 0 && c.is_uppercase() {
+            result.push('_');
+        }
+        result.push(c.to_ascii_lowercase());
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::path;
+
+    #[test]
+    fn to_camel_case_works() {
+        assert_eq!(path!["user_profile", 0, "created_at"].to_camel_case(), path!["userProfile", 0, "createdAt"]);
+        assert_eq!(path!["id", "a__b", "_x", ""].to_camel_case(), path!["id", "aB", "x", ""]);
+    }
+
+    #[test]
+    fn to_pascal_case_works() {
+        assert_eq!(path!["user_profile", 3, "http_code_2"].to_pascal_case(), path!["UserProfile", 3, "HttpCode2"]);
+    }
+
+    #[test]
+    fn to_snake_case_works() {
+        assert_eq!(path!["userProfile", 0, "CreatedAt"].to_snake_case(), path!["user_profile", 0, "created_at"]);
+        assert_eq!(path!["HTTPCode", "already_snake", "v2Beta"].to_snake_case(), path!["h_t_t_p_code", "already_snake", "v2_beta"]);
+    }
+
+    #[test]
+    fn to_kebab_case_works() {
+        assert_eq!(path!["userProfile", 1, "created_at"].to_kebab_case(), path!["user-profile", 1, "created-at"]);
+    }
+
+    #[test]
+    fn case_conversion_round_trips_serde_field_names() {
+        let path = path!["user_id", 2, "display_name"];
+        assert_eq!(path.to_camel_case().to_snake_case(), path);
+        assert_eq!(path.to_pascal_case().to_snake_case(), path);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{Item, KeyPath};
+
+/// How two paths differ, returned by `KeyPath::diff`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PathDiff<'a> {
+    prefix: &'a [Item],
+    self_suffix: &'a [Item],
+    other_suffix: &'a [Item],
+}
+
+impl<'a> PathDiff<'a> {
+
+    /// The items both paths start with.
+    pub fn prefix(&self) -> &'a [Item] {
+        self.prefix
+    }
+
+    /// The number of items both paths start with, which is also the position
+    /// where they diverge.
+    pub fn prefix_len(&self) -> usize {
+        self.prefix.len()
+    }
+
+    /// The items of the path `diff` was called on after the shared prefix.
+    pub fn self_suffix(&self) -> &'a [Item] {
+        self.self_suffix
+    }
+
+    /// The items of the other path after the shared prefix.
+    pub fn other_suffix(&self) -> &'a [Item] {
+        self.other_suffix
+    }
+
+    pub fn is_equal(&self) -> bool {
+        self.self_suffix.is_empty() && self.other_suffix.is_empty()
+    }
+}
+
+impl KeyPath {
+
+    /// Splits both paths at the first position where they differ.
+    pub fn diff<'a>(&'a self, other: &'a KeyPath) -> PathDiff<'a> {
+        let len = self.common_prefix_len(other);
+        PathDiff { prefix: &self[..len], self_suffix: &self[len..], other_suffix: &other[len..] }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::path;
+
+    #[test]
+    fn diff_works() {
+        let (a, b) = (path!["a", "b", "c"], path!["a", "x"]);
+        let diff = a.diff(&b);
+        assert_eq!(diff.prefix_len(), 1);
+        assert_eq!(diff.prefix(), &path!["a"][..]);
+        assert_eq!(diff.self_suffix(), &path!["b", "c"][..]);
+        assert_eq!(diff.other_suffix(), &path!["x"][..]);
+        assert!(!diff.is_equal());
+    }
+
+    #[test]
+    fn diff_works_for_equal_paths() {
+        let path = path!["a", 1];
+        let diff = path.diff(&path);
+        assert_eq!(diff.prefix_len(), 2);
+        assert!(diff.self_suffix().is_empty() && diff.other_suffix().is_empty());
+        assert!(diff.is_equal());
+    }
+
+    #[test]
+    fn diff_works_for_prefixes() {
+        let (a, b) = (path!["a", 1], path!["a", 1, "b"]);
+        assert_eq!(a.diff(&b).self_suffix(), &[]);
+        assert_eq!(a.diff(&b).other_suffix(), &path!["b"][..]);
+        assert_eq!(b.diff(&a).self_suffix(), &path!["b"][..]);
+        assert_eq!(path!["a", 1].diff(&path!["a", "1"]).prefix_len(), 1);
+    }
+}
@@ -0,0 +1,125 @@
+use alloc::string::{String, ToString};
+use core::fmt::{Display, Formatter};
+use crate::{Item, KeyPath};
+
+/// Error returned by `KeyPath::to_env_var` for an item that can't be written
+/// into an environment variable name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EnvVarError {
+    KeyContainsSeparator { position: usize, key: String },
+    NextIndex { position: usize },
+}
+
+impl Display for EnvVarError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        use EnvVarError::*;
+        match self {
+            KeyContainsSeparator { position, key } => write!(f, "key `{}` at position {} contains the separator", key, position),
+            NextIndex { position } => write!(f, "next index at position {} has no environment variable form", position),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EnvVarError { }
+
+impl KeyPath {
+
+    /// Renders the path as an environment variable name, uppercasing keys and
+    /// joining the items with `sep`: `database.pool.max_size` becomes
+    /// `DATABASE__POOL__MAX_SIZE` with `sep` set to `__`.
+    ///
+    /// Keys that contain `sep` once uppercased are rejected, as they would
+    /// read back as several items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sep` is empty.
+    pub fn to_env_var(&self, sep: &str) -> Result<String, EnvVarError> {
+        assert!(!sep.is_empty(), "separator must not be empty");
+        let mut result = String::new();
+        for (position, item) in self.iter().enumerate() {
+            if position > 0 {
+                result.push_str(sep);
+            }
+            match item {
+                Item::Key(key) => {
+                    let segment = key.to_uppercase();
+                    if segment.contains(sep) {
+                        return Err(EnvVarError::KeyContainsSeparator { position, key: key.to_string() });
+                    }
+                    result.push_str(&segment);
+                }
+                Item::Index(index) => result.push_str(&index.to_string()),
+                Item::NextIndex => return Err(EnvVarError::NextIndex { position }),
+            }
+        }
+        Ok(result)
+    }
+
+    /// Parses an environment variable name produced by `to_env_var`,
+    /// lowercasing keys. The name is split on `sep` before lowercasing, and
+    /// segments made of digits become indices as in `split_str`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sep` is empty.
+    pub fn from_env_var(name: &str, sep: &str) -> KeyPath {
+        KeyPath::split_str(name, sep).map_items(|item| match item {
+            Item::Key(key) => Item::Key(key.to_lowercase().into()),
+            item => item,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path;
+
+    #[test]
+    fn to_env_var_works() {
+        assert_eq!(path!["database", "pool", "max_size"].to_env_var("__"), Ok("DATABASE__POOL__MAX_SIZE".to_string()));
+        assert_eq!(path!["servers", 0, "host"].to_env_var("__"), Ok("SERVERS__0__HOST".to_string()));
+        assert_eq!(KeyPath::default().to_env_var("__"), Ok("".to_string()));
+    }
+
+    #[test]
+    fn to_env_var_rejects_keys_containing_the_separator() {
+        assert_eq!(path!["a", "b__c"].to_env_var("__"), Err(EnvVarError::KeyContainsSeparator { position: 1, key: "b__c".to_string() }));
+        assert_eq!(path!["max_size"].to_env_var("_"), Err(EnvVarError::KeyContainsSeparator { position: 0, key: "max_size".to_string() }));
+        assert_eq!(path!["a", "axb"].to_env_var("X"), Err(EnvVarError::KeyContainsSeparator { position: 1, key: "axb".to_string() }));
+    }
+
+    #[test]
+    fn to_env_var_rejects_next_index() {
+        let path = KeyPath::new(vec![Item::Key("a".into()), Item::NextIndex]);
+        assert_eq!(path.to_env_var("__"), Err(EnvVarError::NextIndex { position: 1 }));
+    }
+
+    #[test]
+    fn from_env_var_works() {
+        assert_eq!(KeyPath::from_env_var("DATABASE__POOL__MAX_SIZE", "__"), path!["database", "pool", "max_size"]);
+        assert_eq!(KeyPath::from_env_var("SERVERS__0__HOST", "__"), path!["servers", 0, "host"]);
+        assert_eq!(KeyPath::from_env_var("", "__"), KeyPath::default());
+    }
+
+    #[test]
+    fn from_env_var_works_with_uppercase_separators() {
+        assert_eq!(KeyPath::from_env_var("DATABASE_X_POOL_X_0", "_X_"), path!["database", "pool", 0]);
+    }
+
+    #[test]
+    fn env_var_round_trips_with_uppercase_separators() {
+        let path = path!["database", "pool", 2];
+        let name = path.to_env_var("_X_").unwrap();
+        assert_eq!(name, "DATABASE_X_POOL_X_2");
+        assert_eq!(KeyPath::from_env_var(&name, "_X_"), path);
+    }
+
+    #[test]
+    fn env_var_round_trips_for_lowercase_keys() {
+        let path = path!["database", "replicas", 2, "max_size"];
+        assert_eq!(KeyPath::from_env_var(&path.to_env_var("__").unwrap(), "__"), path);
+    }
+}
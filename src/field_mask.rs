@@ -0,0 +1,118 @@
+//! Interop with protobuf `google.protobuf.FieldMask` paths.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use crate::{Item, KeyPath};
+
+/// Error returned by `KeyPath::to_field_mask_path` for items a field mask
+/// path can't hold, identified by their position in the path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldMaskError {
+    Index { position: usize },
+    InvalidKey { position: usize, key: String },
+}
+
+impl Display for FieldMaskError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        use FieldMaskError::*;
+        match self {
+            Index { position } => write!(f, "field masks can't address array elements, found an index at position {}", position),
+            InvalidKey { position, key } => write!(f, "key `{}` at position {} is not a valid field name", key, position),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FieldMaskError { }
+
+impl KeyPath {
+
+    /// Renders the path as a field mask path like `user.display_name`.
+    ///
+    /// Field masks only address message fields, so indices and keys that are
+    /// empty or contain `.` are rejected.
+    pub fn to_field_mask_path(&self) -> Result<String, FieldMaskError> {
+        let mut result = String::new();
+        for (position, item) in self.iter().enumerate() {
+            match item {
+                Item::Key(key) if key.is_empty() || key.contains('.') => {
+                    return Err(FieldMaskError::InvalidKey { position, key: key.to_string() });
+                }
+                Item::Key(key) => {
+                    if position > 0 {
+                        result.push('.');
+                    }
+                    result.push_str(key);
+                }
+                Item::Index(_) | Item::NextIndex => return Err(FieldMaskError::Index { position }),
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Removes duplicates and paths that are covered by another path in the set,
+/// so `["a.b", "a", "c"]` becomes `["a", "c"]`.
+///
+/// The result is sorted by `KeyPath`'s `Ord`, so it doesn't depend on the
+/// order of the input.
+pub fn normalize(paths: &[KeyPath]) -> Vec<KeyPath> {
+    let mut sorted = paths.to_vec();
+    sorted.sort();
+    let mut result: Vec<KeyPath> = Vec::with_capacity(sorted.len());
+    for path in sorted {
+        if !result.last().is_some_and(|last| path.starts_with(last)) {
+            result.push(path);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path;
+
+    #[test]
+    fn to_field_mask_path_works() {
+        assert_eq!(path!["user", "display_name"].to_field_mask_path(), Ok("user.display_name".to_owned()));
+        assert_eq!(KeyPath::default().to_field_mask_path(), Ok("".to_owned()));
+    }
+
+    #[test]
+    fn to_field_mask_path_rejects_indices() {
+        let result = path!["users", 3, "name"].to_field_mask_path();
+        assert_eq!(result, Err(FieldMaskError::Index { position: 1 }));
+        assert_eq!(result.unwrap_err().to_string(), "field masks can't address array elements, found an index at position 1");
+    }
+
+    #[test]
+    fn to_field_mask_path_rejects_invalid_keys() {
+        assert_eq!(path!["a", "b.c"].to_field_mask_path(), Err(FieldMaskError::InvalidKey { position: 1, key: "b.c".to_owned() }));
+        assert_eq!(path![""].to_field_mask_path(), Err(FieldMaskError::InvalidKey { position: 0, key: "".to_owned() }));
+    }
+
+    #[test]
+    fn normalize_removes_descendants() {
+        assert_eq!(normalize(&[path!["a"], path!["a", "b"]]), vec![path!["a"]]);
+        assert_eq!(normalize(&[path!["a", "b", "c"], path!["a", "b"], path!["a", "x"]]), vec![path!["a", "b"], path!["a", "x"]]);
+    }
+
+    #[test]
+    fn normalize_keeps_siblings_with_shared_text() {
+        assert_eq!(normalize(&[path!["ab"], path!["a"]]), vec![path!["a"], path!["ab"]]);
+    }
+
+    #[test]
+    fn normalize_sorts_and_dedups() {
+        let paths = [path!["c"], path!["b", "x"], path!["a"], path!["c"], path!["b", "x"]];
+        assert_eq!(normalize(&paths), vec![path!["a"], path!["b", "x"], path!["c"]]);
+        assert_eq!(normalize(&[]), Vec::<KeyPath>::new());
+    }
+
+    #[test]
+    fn normalize_collapses_everything_under_the_empty_path() {
+        assert_eq!(normalize(&[path!["a"], KeyPath::default(), path!["b"]]), vec![KeyPath::default()]);
+    }
+}
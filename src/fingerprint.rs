@@ -0,0 +1,63 @@
+use crate::{Item, KeyPath};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+impl KeyPath {
+
+    /// A 64-bit hash of the path that is the same on every platform, in every
+    /// run and in every version of this crate, for use as a persisted
+    /// identifier. Changing the output is considered a breaking change.
+    ///
+    /// The hash is 64-bit FNV-1a over an encoding of the items in order. A key
+    /// is the byte `0x00`, its UTF-8 length as a little-endian `u64` and its
+    /// bytes, an index is `0x01` followed by its value as a little-endian
+    /// `u64`, and `Item::NextIndex` is the single byte `0x02`. Distinct paths
+    /// have distinct encodings, so `path!["a", 1]` and `path!["a", "1"]` hash
+    /// differently, but like any 64-bit hash collisions are possible.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash = FNV_OFFSET_BASIS;
+        let mut feed = |bytes: &[u8]| {
+            for byte in bytes {
+                hash ^= *byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        };
+        for item in self.iter() {
+            match item {
+                Item::Key(key) => {
+                    feed(&[0x00]);
+                    feed(&(key.len() as u64).to_le_bytes());
+                    feed(key.as_bytes());
+                }
+                Item::Index(index) => {
+                    feed(&[0x01]);
+                    feed(&(*index as u64).to_le_bytes());
+                }
+                Item::NextIndex => feed(&[0x02]),
+            }
+        }
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path;
+
+    #[test]
+    fn fingerprint_is_stable() {
+        assert_eq!(KeyPath::default().fingerprint(), 0xcbf29ce484222325);
+        assert_eq!(path!["a", 1].fingerprint(), 0x88c62bf59fa411c5);
+        assert_eq!(path!["a", "1"].fingerprint(), 0xfe9efea69de484c5);
+        assert_eq!(path!["where", "items", 5, "name"].fingerprint(), 0x701da5da44af2d63);
+        assert_eq!(KeyPath::new(vec![Item::Key("a".into()), Item::NextIndex]).fingerprint(), 0xb12a53d88161003d);
+    }
+
+    #[test]
+    fn fingerprint_separates_keys() {
+        assert_eq!(path!["ab"].fingerprint(), 0x95f246d4fbe15c02);
+        assert_eq!(path!["a", "b"].fingerprint(), 0xfe9f4ba69de5079c);
+    }
+}
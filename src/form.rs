@@ -0,0 +1,201 @@
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use core::fmt::{Display, Formatter};
+use crate::{Item, KeyPath, KeyPathParseError, KeyPathParseErrorKind::*};
+use crate::parse::unexpected_char;
+
+/// Error returned by `KeyPath::to_form_name` for an item that wouldn't read
+/// back as itself.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FormNameError {
+    EmptyKey { position: usize },
+    BracketInKey { position: usize, key: String },
+    NumericKey { position: usize, key: String },
+    NextIndex { position: usize },
+}
+
+impl FormNameError {
+
+    /// The position of the offending item in the path.
+    pub fn position(&self) -> usize {
+        use FormNameError::*;
+        match self {
+            EmptyKey { position } | BracketInKey { position, .. } | NumericKey { position, .. } | NextIndex { position } => *position,
+        }
+    }
+}
+
+impl Display for FormNameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        use FormNameError::*;
+        match self {
+            EmptyKey { position } => write!(f, "empty key at position {} has no form name", position),
+            BracketInKey { position, key } => write!(f, "key `{}` at position {} contains a bracket", key, position),
+            NumericKey { position, key } => write!(f, "key `{}` at position {} would read back as an index", key, position),
+            NextIndex { position } => write!(f, "next index at position {} has no form name", position),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FormNameError { }
+
+/// What `KeyPath::from_form_name_with` does with empty brackets like `tags[]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmptyBrackets {
+    Reject,
+    Append,
+}
+
+impl KeyPath {
+
+    /// Parses HTML form field names like `user[addresses][0][street]`,
+    /// rejecting empty brackets.
+    pub fn from_form_name(s: &str) -> Result<Self, KeyPathParseError> {
+        Self::from_form_name_with(s, EmptyBrackets::Reject)
+    }
+
+    /// Parses HTML form field names like `user[addresses][0][street]`.
+    ///
+    /// Segments made of ASCII digits become indices and everything else keys.
+    /// Empty brackets either fail or become `Item::NextIndex`.
+    pub fn from_form_name_with(s: &str, empty_brackets: EmptyBrackets) -> Result<Self, KeyPathParseError> {
+        if s.is_empty() {
+            return Ok(KeyPath::default());
+        }
+        let base_len = s.find(['[', ']']).unwrap_or(s.len());
+        if base_len == 0 {
+            return Err(KeyPathParseError::new(EmptySegment, 0, ""));
+        }
+        let mut items = vec![parse_form_segment(&s[..base_len])];
+        let mut pos = base_len;
+        while pos < s.len() {
+            let Some(rest) = s[pos..].strip_prefix('[') else {
+                return Err(unexpected_char(s, pos));
+            };
+            let close = rest.find(['[', ']']).ok_or_else(|| KeyPathParseError::new(UnclosedBracket, pos, &s[pos..]))?;
+            if !rest[close..].starts_with(']') {
+                return Err(unexpected_char(s, pos + 1 + close));
+            }
+            let segment = &rest[..close];
+            items.push(match (segment.is_empty(), empty_brackets) {
+                (true, EmptyBrackets::Reject) => return Err(KeyPathParseError::new(EmptySegment, pos + 1, "")),
+                (true, EmptyBrackets::Append) => Item::NextIndex,
+                (false, _) => parse_form_segment(segment),
+            });
+            pos += close + 2;
+        }
+        Ok(KeyPath::new(items))
+    }
+
+    /// Renders the path as an HTML form field name like
+    /// `user[addresses][0][street]`, with `Item::NextIndex` as `[]`, which
+    /// reads back with `EmptyBrackets::Append`.
+    ///
+    /// Form names have no escapes, so keys that are empty, contain `[` or `]`
+    /// or are made of digits are rejected, as is a leading `Item::NextIndex`.
+    pub fn to_form_name(&self) -> Result<String, FormNameError> {
+        let mut result = String::new();
+        for (position, item) in self.iter().enumerate() {
+            if position > 0 {
+                result.push('[');
+            }
+            match item {
+                Item::Key(key) if key.is_empty() => return Err(FormNameError::EmptyKey { position }),
+                Item::Key(key) if key.contains(['[', ']']) => return Err(FormNameError::BracketInKey { position, key: key.to_string() }),
+                Item::Key(key) if matches!(parse_form_segment(key), Item::Index(_)) => return Err(FormNameError::NumericKey { position, key: key.to_string() }),
+                Item::Key(key) => result.push_str(key),
+                Item::Index(index) => result.push_str(&index.to_string()),
+                Item::NextIndex if position == 0 => return Err(FormNameError::NextIndex { position }),
+                Item::NextIndex => (),
+            }
+            if position > 0 {
+                result.push(']');
+            }
+        }
+        Ok(result)
+    }
+}
+
+fn parse_form_segment(segment: &str) -> Item {
+    if segment.bytes().all(|b| b.is_ascii_digit()) {
+        if let Ok(index) = segment.parse() {
+            return Item::Index(index);
+        }
+    }
+    Item::Key(segment.to_owned().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path;
+
+    #[test]
+    fn from_form_name_works() {
+        let result = KeyPath::from_form_name("user[addresses][0][street]").unwrap();
+        assert_eq!(result, path!["user", "addresses", 0, "street"]);
+    }
+
+    #[test]
+    fn from_form_name_works_without_brackets() {
+        assert_eq!(KeyPath::from_form_name("email").unwrap(), path!["email"]);
+    }
+
+    #[test]
+    fn from_form_name_handles_empty_brackets() {
+        assert_eq!(KeyPath::from_form_name("tags[]"), Err(KeyPathParseError::new(EmptySegment, 5, "")));
+        let result = KeyPath::from_form_name_with("tags[]", EmptyBrackets::Append).unwrap();
+        assert_eq!(result, path!["tags"] + Item::NextIndex);
+    }
+
+    #[test]
+    fn from_form_name_errors_on_malformed_names() {
+        assert_eq!(KeyPath::from_form_name("[a]"), Err(KeyPathParseError::new(EmptySegment, 0, "")));
+        assert_eq!(KeyPath::from_form_name("a[b"), Err(KeyPathParseError::new(UnclosedBracket, 1, "[b")));
+        assert_eq!(KeyPath::from_form_name("a[b]c"), Err(KeyPathParseError::new(UnexpectedChar, 4, "c")));
+        assert_eq!(KeyPath::from_form_name("a[b[c]]"), Err(KeyPathParseError::new(UnexpectedChar, 3, "[")));
+        assert_eq!(KeyPath::from_form_name("a]"), Err(KeyPathParseError::new(UnexpectedChar, 1, "]")));
+    }
+
+    #[test]
+    fn to_form_name_works() {
+        let path = path!["user", "addresses", 0, "street"];
+        assert_eq!(path.to_form_name(), Ok("user[addresses][0][street]".to_owned()));
+        assert_eq!(path!["email"].to_form_name(), Ok("email".to_owned()));
+        assert_eq!((path!["tags"] + Item::NextIndex).to_form_name(), Ok("tags[]".to_owned()));
+        assert_eq!(KeyPath::default().to_form_name(), Ok("".to_owned()));
+    }
+
+    #[test]
+    fn to_form_name_rejects_unrepresentable_items() {
+        assert_eq!(path!["a[b]"].to_form_name(), Err(FormNameError::BracketInKey { position: 0, key: "a[b]".to_owned() }));
+        assert_eq!(path!["a", "x]y"].to_form_name(), Err(FormNameError::BracketInKey { position: 1, key: "x]y".to_owned() }));
+        assert_eq!(path!["a", ""].to_form_name(), Err(FormNameError::EmptyKey { position: 1 }));
+        assert_eq!(path!["", "a"].to_form_name(), Err(FormNameError::EmptyKey { position: 0 }));
+        assert_eq!(path!["a", "12"].to_form_name(), Err(FormNameError::NumericKey { position: 1, key: "12".to_owned() }));
+        assert_eq!(KeyPath::new(vec![Item::NextIndex]).to_form_name(), Err(FormNameError::NextIndex { position: 0 }));
+    }
+
+    #[test]
+    fn form_name_round_trips() {
+        let paths = [
+            path!["user", "addresses", 0, "street"],
+            path![0, "a b", "007x", 12],
+            path!["tags"] + Item::NextIndex,
+            KeyPath::default(),
+        ];
+        for path in paths {
+            let name = path.to_form_name().unwrap();
+            assert_eq!(KeyPath::from_form_name_with(&name, EmptyBrackets::Append).unwrap(), path);
+        }
+    }
+
+    #[test]
+    fn form_name_error_works() {
+        let error = path!["a", "x]y"].to_form_name().unwrap_err();
+        assert_eq!(error.position(), 1);
+        assert_eq!(error.to_string(), "key `x]y` at position 1 contains a bracket");
+    }
+}
@@ -0,0 +1,218 @@
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use core::fmt::{Display, Formatter};
+use crate::{Item, KeyPath, KeyPathParseError, KeyPathParseErrorKind::*};
+use crate::json_string::{parse_json_string, push_json_string};
+use crate::parse::unexpected_char;
+
+const KEYWORDS: &[&str] = &[
+    "__loc__", "and", "as", "catch", "def", "elif", "else", "end", "foreach",
+    "if", "import", "include", "label", "not", "or", "reduce", "then", "try",
+];
+
+/// Error returned by `KeyPath::to_jq` for an `Item::NextIndex`, which has no
+/// jq path expression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JqError {
+    position: usize,
+}
+
+impl JqError {
+
+    /// The position of the `Item::NextIndex` in the path.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl Display for JqError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "next index at position {} has no jq path expression", self.position)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for JqError { }
+
+impl KeyPath {
+
+    /// Renders the path as a jq path expression like `.a.b[0]`.
+    ///
+    /// Keys that aren't plain identifiers are quoted, as in `.["weird key"]`,
+    /// and the empty path is `.`, the identity filter. `Item::NextIndex` is
+    /// rejected, as jq's `[]` iterates over all elements instead.
+    pub fn to_jq(&self) -> Result<String, JqError> {
+        if self.is_empty() {
+            return Ok(".".to_owned());
+        }
+        let mut result = String::new();
+        for (i, item) in self.iter().enumerate() {
+            match item {
+                Item::Key(key) if is_jq_identifier(key) => {
+                    result.push('.');
+                    result.push_str(key);
+                }
+                Item::Key(key) => {
+                    if i == 0 {
+                        result.push('.');
+                    }
+                    result.push('[');
+                    push_json_string(&mut result, key);
+                    result.push(']');
+                }
+                Item::Index(index) => {
+                    if i == 0 {
+                        result.push('.');
+                    }
+                    result.push('[');
+                    result.push_str(&index.to_string());
+                    result.push(']');
+                }
+                Item::NextIndex => return Err(JqError { position: i }),
+            }
+        }
+        Ok(result)
+    }
+
+    /// Parses the subset of jq path expressions made of `.name`, `."name"`,
+    /// `["name"]` and `[0]` steps, as produced by `to_jq`.
+    pub fn from_jq(s: &str) -> Result<Self, KeyPathParseError> {
+        if !s.starts_with('.') {
+            return Err(if s.is_empty() {
+                KeyPathParseError::new(EmptySegment, 0, "")
+            } else {
+                unexpected_char(s, 0)
+            });
+        }
+        if s == "." {
+            return Ok(KeyPath::default());
+        }
+        let mut items = vec![];
+        let mut pos = 0;
+        while pos < s.len() {
+            let rest = &s[pos..];
+            if let Some(after_dot) = rest.strip_prefix('.') {
+                pos += 1;
+                if after_dot.starts_with('[') {
+                    continue;
+                }
+                if after_dot.starts_with('"') {
+                    let (key, len) = parse_json_string(after_dot, pos)?;
+                    items.push(Item::Key(key.into()));
+                    pos += len;
+                    continue;
+                }
+                let len = identifier_len(after_dot);
+                if len == 0 {
+                    return Err(if after_dot.is_empty() || after_dot.starts_with('.') {
+                        KeyPathParseError::new(EmptySegment, pos, "")
+                    } else {
+                        unexpected_char(s, pos)
+                    });
+                }
+                items.push(Item::Key(after_dot[..len].to_owned().into()));
+                pos += len;
+            } else if let Some(inner) = rest.strip_prefix('[') {
+                if inner.starts_with('"') {
+                    let (key, len) = parse_json_string(inner, pos + 1)?;
+                    if !inner[len..].starts_with(']') {
+                        return Err(KeyPathParseError::new(UnclosedBracket, pos, rest));
+                    }
+                    items.push(Item::Key(key.into()));
+                    pos += len + 2;
+                } else {
+                    let close = inner.find(']').ok_or_else(|| KeyPathParseError::new(UnclosedBracket, pos, rest))?;
+                    let segment = &inner[..close];
+                    if segment.is_empty() || !segment.bytes().all(|b| b.is_ascii_digit()) {
+                        return Err(KeyPathParseError::new(InvalidIndex, pos + 1, segment));
+                    }
+                    let index = segment.parse().map_err(|_| KeyPathParseError::new(IndexOverflow, pos + 1, segment))?;
+                    items.push(Item::Index(index));
+                    pos += close + 2;
+                }
+            } else {
+                return Err(unexpected_char(s, pos));
+            }
+        }
+        Ok(KeyPath::new(items))
+    }
+}
+
+fn identifier_len(s: &str) -> usize {
+    match s.bytes().next() {
+        Some(b) if b.is_ascii_alphabetic() || b == b'_' => s.bytes().take_while(|b| b.is_ascii_alphanumeric() || *b == b'_').count(),
+        _ => 0,
+    }
+}
+
+fn is_jq_identifier(key: &str) -> bool {
+    !key.is_empty() && identifier_len(key) == key.len() && !KEYWORDS.contains(&key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path;
+
+    #[test]
+    fn to_jq_works() {
+        assert_eq!(path!["a", "b", 0].to_jq().unwrap(), ".a.b[0]");
+        assert_eq!(path![0, "a", 1, 2].to_jq().unwrap(), ".[0].a[1][2]");
+    }
+
+    #[test]
+    fn to_jq_works_for_empty() {
+        assert_eq!(KeyPath::default().to_jq().unwrap(), ".");
+    }
+
+    #[test]
+    fn to_jq_quotes_non_identifier_keys() {
+        assert_eq!(path!["weird key"].to_jq().unwrap(), r#".["weird key"]"#);
+        assert_eq!(path!["a", "b-c", "1x", "if", "say \"hi\"\n"].to_jq().unwrap(), r#".a["b-c"]["1x"]["if"]["say \"hi\"\n"]"#);
+        assert_eq!(path!["_ok", "Ok2", ""].to_jq().unwrap(), r#"._ok.Ok2[""]"#);
+    }
+
+    #[test]
+    fn to_jq_rejects_next_index() {
+        let error = (path!["items"] + Item::NextIndex).to_jq().unwrap_err();
+        assert_eq!(error.position(), 1);
+        assert_eq!(error.to_string(), "next index at position 1 has no jq path expression");
+        assert_eq!(KeyPath::new(vec![Item::NextIndex]).to_jq(), Err(JqError { position: 0 }));
+    }
+
+    #[test]
+    fn from_jq_works() {
+        assert_eq!(KeyPath::from_jq(".a.b[0]").unwrap(), path!["a", "b", 0]);
+        assert_eq!(KeyPath::from_jq(".").unwrap(), KeyPath::default());
+        assert_eq!(KeyPath::from_jq(r#".["weird key"]."quotedA"[1].[2]"#).unwrap(), path!["weird key", "quotedA", 1, 2]);
+        assert_eq!(KeyPath::from_jq(r#".["caf\u00e9\/"]"#).unwrap(), path!["café/"]);
+    }
+
+    #[test]
+    fn from_jq_decodes_surrogate_pairs() {
+        assert_eq!(KeyPath::from_jq(r#".["\ud83d\ude00"]"#).unwrap(), path!["😀"]);
+        assert_eq!(KeyPath::from_jq(r#".["a\uD834\uDD1Eb"]"#).unwrap(), path!["a𝄞b"]);
+        assert_eq!(KeyPath::from_jq(r#".["\ud83d"]"#), Err(KeyPathParseError::new(InvalidEscape, 3, r"\ud83d")));
+        assert_eq!(KeyPath::from_jq(r#".["\ud83d\u0041"]"#), Err(KeyPathParseError::new(InvalidEscape, 3, r"\ud83d")));
+        assert_eq!(KeyPath::from_jq(r#".["\ude00"]"#), Err(KeyPathParseError::new(InvalidEscape, 3, r"\ude00")));
+    }
+
+    #[test]
+    fn jq_round_trips() {
+        let path = path!["a", "weird key", 0, "", "if", "\\\"\t", 12, "ü"];
+        assert_eq!(KeyPath::from_jq(&path.to_jq().unwrap()).unwrap(), path);
+    }
+
+    #[test]
+    fn from_jq_errors_on_invalid_input() {
+        assert_eq!(KeyPath::from_jq("a"), Err(KeyPathParseError::new(UnexpectedChar, 0, "a")));
+        assert_eq!(KeyPath::from_jq(""), Err(KeyPathParseError::new(EmptySegment, 0, "")));
+        assert_eq!(KeyPath::from_jq(".a."), Err(KeyPathParseError::new(EmptySegment, 3, "")));
+        assert_eq!(KeyPath::from_jq(".a[0"), Err(KeyPathParseError::new(UnclosedBracket, 2, "[0")));
+        assert_eq!(KeyPath::from_jq(".a[x]"), Err(KeyPathParseError::new(InvalidIndex, 3, "x")));
+        assert_eq!(KeyPath::from_jq(".0"), Err(KeyPathParseError::new(UnexpectedChar, 1, "0")));
+        assert_eq!(KeyPath::from_jq(r#".["a\q"]"#), Err(KeyPathParseError::new(InvalidEscape, 4, r"\q")));
+        assert_eq!(KeyPath::from_jq(r#"."a"#), Err(KeyPathParseError::new(UnclosedQuote, 1, r#""a"#)));
+    }
+}
@@ -0,0 +1,165 @@
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use crate::{Item, KeyPath, KeyPathParseError, KeyPathParseErrorKind::*};
+use crate::json_string::{parse_json_string, push_json_string};
+use crate::parse::unexpected_char;
+
+impl KeyPath {
+
+    /// Renders the path as a JSONPath query like `$.store.book[0].title`.
+    ///
+    /// Keys that aren't valid member name shorthands are quoted, as in
+    /// `$["weird key"]`, and the empty path is `$`. JSONPath has no
+    /// counterpart of `Item::NextIndex`, which renders as `[-]`.
+    pub fn to_json_path(&self) -> String {
+        let mut result = "$".to_owned();
+        for item in self {
+            match item {
+                Item::Key(key) if !key.is_empty() && member_name_len(key) == key.len() => {
+                    result.push('.');
+                    result.push_str(key);
+                }
+                Item::Key(key) => {
+                    result.push('[');
+                    push_json_string(&mut result, key);
+                    result.push(']');
+                }
+                Item::Index(index) => {
+                    result.push('[');
+                    result.push_str(&index.to_string());
+                    result.push(']');
+                }
+                Item::NextIndex => result.push_str("[-]"),
+            }
+        }
+        result
+    }
+
+    /// Parses the subset of JSONPath made of `$` followed by `.name`,
+    /// `["name"]` and `[0]` steps, as produced by `to_json_path`.
+    ///
+    /// Wildcards, descendant segments, filters, slices, negative indices and
+    /// single quoted names are rejected rather than approximated.
+    pub fn from_json_path(s: &str) -> Result<Self, KeyPathParseError> {
+        if !s.starts_with('$') {
+            return Err(if s.is_empty() {
+                KeyPathParseError::new(EmptySegment, 0, "")
+            } else {
+                unexpected_char(s, 0)
+            });
+        }
+        let mut items = vec![];
+        let mut pos = 1;
+        while pos < s.len() {
+            let rest = &s[pos..];
+            if let Some(after_dot) = rest.strip_prefix('.') {
+                pos += 1;
+                let len = member_name_len(after_dot);
+                if len == 0 {
+                    return Err(if after_dot.is_empty() {
+                        KeyPathParseError::new(EmptySegment, pos, "")
+                    } else {
+                        unexpected_char(s, pos)
+                    });
+                }
+                items.push(Item::Key(after_dot[..len].to_owned().into()));
+                pos += len;
+            } else if let Some(inner) = rest.strip_prefix('[') {
+                if inner.starts_with('"') {
+                    let (key, len) = parse_json_string(inner, pos + 1)?;
+                    if !inner[len..].starts_with(']') {
+                        return Err(KeyPathParseError::new(UnclosedBracket, pos, rest));
+                    }
+                    items.push(Item::Key(key.into()));
+                    pos += len + 2;
+                } else if inner.starts_with(|c: char| c.is_ascii_digit() || c == '-') {
+                    let close = inner.find(']').ok_or_else(|| KeyPathParseError::new(UnclosedBracket, pos, rest))?;
+                    let segment = &inner[..close];
+                    if segment == "-" {
+                        items.push(Item::NextIndex);
+                    } else if segment.bytes().all(|b| b.is_ascii_digit()) {
+                        let index = segment.parse().map_err(|_| KeyPathParseError::new(IndexOverflow, pos + 1, segment))?;
+                        items.push(Item::Index(index));
+                    } else {
+                        return Err(KeyPathParseError::new(InvalidIndex, pos + 1, segment));
+                    }
+                    pos += close + 2;
+                } else if inner.is_empty() {
+                    return Err(KeyPathParseError::new(UnclosedBracket, pos, rest));
+                } else {
+                    return Err(unexpected_char(s, pos + 1));
+                }
+            } else {
+                return Err(unexpected_char(s, pos));
+            }
+        }
+        Ok(KeyPath::new(items))
+    }
+}
+
+/// Length in bytes of the member name shorthand at the start of `s`: a letter,
+/// `_` or non-ASCII character followed by any of those or digits.
+fn member_name_len(s: &str) -> usize {
+    let is_first = |c: char| c.is_ascii_alphabetic() || c == '_' || !c.is_ascii();
+    match s.chars().next() {
+        Some(c) if is_first(c) => s.find(|c: char| !is_first(c) && !c.is_ascii_digit()).unwrap_or(s.len()),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path;
+
+    #[test]
+    fn to_json_path_works() {
+        assert_eq!(&path!["store", "book", 0, "title"].to_json_path(), "$.store.book[0].title");
+        assert_eq!(&path![0, "a", 1, 2].to_json_path(), "$[0].a[1][2]");
+        assert_eq!(&KeyPath::default().to_json_path(), "$");
+    }
+
+    #[test]
+    fn to_json_path_quotes_keys_when_needed() {
+        assert_eq!(&path!["weird key"].to_json_path(), r#"$["weird key"]"#);
+        assert_eq!(&path!["a.b", "1x", "", "say \"hi\""].to_json_path(), r#"$["a.b"]["1x"][""]["say \"hi\""]"#);
+        assert_eq!(&path!["_ok", "Ok2", "café"].to_json_path(), "$._ok.Ok2.café");
+    }
+
+    #[test]
+    fn from_json_path_works() {
+        assert_eq!(KeyPath::from_json_path("$.store.book[0].title").unwrap(), path!["store", "book", 0, "title"]);
+        assert_eq!(KeyPath::from_json_path("$").unwrap(), KeyPath::default());
+        assert_eq!(KeyPath::from_json_path(r#"$["weird key"][1]"#).unwrap(), path!["weird key", 1]);
+        assert_eq!(KeyPath::from_json_path("$.a[-]").unwrap(), KeyPath::new(vec![Item::Key("a".into()), Item::NextIndex]));
+        assert_eq!(KeyPath::from_json_path(r#"$["\ud83d\ude00"]"#).unwrap(), path!["😀"]);
+    }
+
+    #[test]
+    fn json_path_round_trips() {
+        let path = path!["a", "weird key", 0, "", "true", "\\\"\t", 12, "ü"];
+        assert_eq!(KeyPath::from_json_path(&path.to_json_path()).unwrap(), path);
+    }
+
+    #[test]
+    fn from_json_path_rejects_unsupported_constructs() {
+        assert_eq!(KeyPath::from_json_path("$.*"), Err(KeyPathParseError::new(UnexpectedChar, 2, "*")));
+        assert_eq!(KeyPath::from_json_path("$..a"), Err(KeyPathParseError::new(UnexpectedChar, 2, ".")));
+        assert_eq!(KeyPath::from_json_path("$[*]"), Err(KeyPathParseError::new(UnexpectedChar, 2, "*")));
+        assert_eq!(KeyPath::from_json_path("$[?@.a]"), Err(KeyPathParseError::new(UnexpectedChar, 2, "?")));
+        assert_eq!(KeyPath::from_json_path("$['a']"), Err(KeyPathParseError::new(UnexpectedChar, 2, "'")));
+        assert_eq!(KeyPath::from_json_path("$[-1]"), Err(KeyPathParseError::new(InvalidIndex, 2, "-1")));
+        assert_eq!(KeyPath::from_json_path("$[1:2]"), Err(KeyPathParseError::new(InvalidIndex, 2, "1:2")));
+    }
+
+    #[test]
+    fn from_json_path_errors_on_invalid_input() {
+        assert_eq!(KeyPath::from_json_path(""), Err(KeyPathParseError::new(EmptySegment, 0, "")));
+        assert_eq!(KeyPath::from_json_path("a.b"), Err(KeyPathParseError::new(UnexpectedChar, 0, "a")));
+        assert_eq!(KeyPath::from_json_path("$.a."), Err(KeyPathParseError::new(EmptySegment, 4, "")));
+        assert_eq!(KeyPath::from_json_path("$.a[0"), Err(KeyPathParseError::new(UnclosedBracket, 3, "[0")));
+        assert_eq!(KeyPath::from_json_path("$.a["), Err(KeyPathParseError::new(UnclosedBracket, 3, "[")));
+        assert_eq!(KeyPath::from_json_path("$a"), Err(KeyPathParseError::new(UnexpectedChar, 1, "a")));
+    }
+}
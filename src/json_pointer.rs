@@ -0,0 +1,143 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use crate::{Item, KeyPath, KeyPathParseError, KeyPathParseErrorKind::*};
+use crate::parse::unexpected_char;
+
+/// Error returned by `KeyPath::from_json_pointer`.
+pub type JsonPointerError = KeyPathParseError;
+
+impl KeyPath {
+
+    /// Renders the path as an RFC 6901 JSON Pointer like `/users/3/email`,
+    /// with `~` in keys escaped as `~0` and `/` as `~1`. The empty path is the
+    /// empty pointer `""`, which refers to the whole document.
+    ///
+    /// JSON Pointer does not tell keys from indices, so a key made of digits
+    /// reads back as an index and a `-` key as `Item::NextIndex`.
+    pub fn to_json_pointer(&self) -> String {
+        let mut result = String::new();
+        for item in self.iter() {
+            result.push('/');
+            push_token(&mut result, item);
+        }
+        result
+    }
+
+    /// Parses an RFC 6901 JSON Pointer, which must be empty or start with `/`.
+    ///
+    /// Tokens that are `0` or digits without a leading zero become indices,
+    /// `-` becomes `Item::NextIndex` and anything else a key. A `~` not
+    /// followed by `0` or `1` is a `KeyPathParseErrorKind::InvalidEscape`.
+    pub fn from_json_pointer(s: &str) -> Result<Self, JsonPointerError> {
+        if !s.is_empty() && !s.starts_with('/') {
+            return Err(unexpected_char(s, 0));
+        }
+        parse_tokens(s, 0).map(KeyPath::new)
+    }
+}
+
+/// Appends `item` as a JSON Pointer reference token, escaping `~` as `~0` and
+/// `/` as `~1`. `Item::NextIndex` is `-`.
+pub(crate) fn push_token(out: &mut String, item: &Item) {
+    match item {
+        Item::Key(key) => {
+            for c in key.chars() {
+                match c {
+                    '~' => out.push_str("~0"),
+                    '/' => out.push_str("~1"),
+                    c => out.push(c),
+                }
+            }
+        }
+        Item::Index(index) => out.push_str(&index.to_string()),
+        Item::NextIndex => out.push('-'),
+    }
+}
+
+/// Parses the reference tokens of a JSON Pointer, which must be empty or
+/// start with `/`. `offset` is the position of `s` in the whole input.
+///
+/// Tokens that are `0` or digits without a leading zero become indices, `-`
+/// becomes `Item::NextIndex` and anything else a key. Numbers too large for
+/// `usize` stay keys, like in `KeyPath::split_str`.
+pub(crate) fn parse_tokens(s: &str, offset: usize) -> Result<Vec<Item>, KeyPathParseError> {
+    let mut items = Vec::new();
+    if s.is_empty() {
+        return Ok(items);
+    }
+    debug_assert!(s.starts_with('/'));
+    let mut start = 1;
+    for token in s[1..].split('/') {
+        items.push(parse_token(token, offset + start)?);
+        start += token.len() + 1;
+    }
+    Ok(items)
+}
+
+fn parse_token(token: &str, offset: usize) -> Result<Item, KeyPathParseError> {
+    if token == "-" {
+        return Ok(Item::NextIndex);
+    }
+    let numeric = token == "0" || (!token.starts_with('0') && !token.is_empty() && token.bytes().all(|b| b.is_ascii_digit()));
+    if let (true, Ok(index)) = (numeric, token.parse()) {
+        return Ok(Item::Index(index));
+    }
+    let mut key = String::with_capacity(token.len());
+    let mut chars = token.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '~' => match chars.next() {
+                Some((_, '0')) => key.push('~'),
+                Some((_, '1')) => key.push('/'),
+                Some((j, c)) => return Err(KeyPathParseError::new(InvalidEscape, offset + i, &token[i..j + c.len_utf8()])),
+                None => return Err(KeyPathParseError::new(InvalidEscape, offset + i, "~")),
+            },
+            c => key.push(c),
+        }
+    }
+    Ok(Item::Key(key.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path;
+
+    #[test]
+    fn to_json_pointer_works() {
+        assert_eq!(path!["users", 3, "email"].to_json_pointer(), "/users/3/email");
+        assert_eq!(path!["a/b", "m~n", "~1"].to_json_pointer(), "/a~1b/m~0n/~01");
+        assert_eq!(path![""].to_json_pointer(), "/");
+        assert_eq!(KeyPath::new(vec![Item::Key("a".into()), Item::NextIndex]).to_json_pointer(), "/a/-");
+        assert_eq!(KeyPath::default().to_json_pointer(), "");
+    }
+
+    #[test]
+    fn from_json_pointer_works() {
+        assert_eq!(KeyPath::from_json_pointer("/users/3/email"), Ok(path!["users", 3, "email"]));
+        assert_eq!(KeyPath::from_json_pointer("/a~1b/m~0n/~01"), Ok(path!["a/b", "m~n", "~1"]));
+        assert_eq!(KeyPath::from_json_pointer("/03/-"), Ok(KeyPath::new(vec![Item::Key("03".into()), Item::NextIndex])));
+        assert_eq!(KeyPath::from_json_pointer("/"), Ok(path![""]));
+        assert_eq!(KeyPath::from_json_pointer(""), Ok(KeyPath::default()));
+    }
+
+    #[test]
+    fn from_json_pointer_round_trips() {
+        let path = path!["a/b", 0, "~", "c d"];
+        assert_eq!(KeyPath::from_json_pointer(&path.to_json_pointer()), Ok(path));
+    }
+
+    #[test]
+    fn from_json_pointer_errors_without_leading_slash() {
+        let error = KeyPath::from_json_pointer("users/3").unwrap_err();
+        assert_eq!(error.kind(), UnexpectedChar);
+        assert_eq!(error.offset(), 0);
+        assert_eq!(error.fragment(), "u");
+    }
+
+    #[test]
+    fn from_json_pointer_errors_on_invalid_escape() {
+        assert_eq!(KeyPath::from_json_pointer("/a/b~2"), Err(KeyPathParseError::new(InvalidEscape, 4, "~2")));
+        assert_eq!(KeyPath::from_json_pointer("/a~"), Err(KeyPathParseError::new(InvalidEscape, 2, "~")));
+    }
+}
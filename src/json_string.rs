@@ -0,0 +1,75 @@
+use alloc::format;
+use alloc::string::String;
+use crate::{KeyPathParseError, KeyPathParseErrorKind::*};
+
+/// Appends `s` as a double quoted JSON string literal.
+pub(crate) fn push_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Reads a JSON string literal from the start of `s`, which must begin with
+/// `"`. Returns the decoded string and the number of bytes consumed. `offset`
+/// is the position of `s` in the whole input, used for errors.
+pub(crate) fn parse_json_string(s: &str, offset: usize) -> Result<(String, usize), KeyPathParseError> {
+    let mut result = String::new();
+    let mut chars = s.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((result, i + 1)),
+            '\\' => {
+                let escape = match chars.next() {
+                    Some((_, '"')) => '"',
+                    Some((_, '\\')) => '\\',
+                    Some((_, '/')) => '/',
+                    Some((_, 'b')) => '\u{8}',
+                    Some((_, 'f')) => '\u{c}',
+                    Some((_, 'n')) => '\n',
+                    Some((_, 'r')) => '\r',
+                    Some((_, 't')) => '\t',
+                    Some((_, 'u')) => {
+                        // Characters outside the BMP are written as a UTF-16
+                        // surrogate pair, `\uD83D\uDE00`.
+                        let (c, len) = match parse_hex4(s, i + 2) {
+                            Some(high @ 0xD800..=0xDBFF) => {
+                                let low = s.get(i + 6..i + 8)
+                                    .filter(|escape| *escape == "\\u")
+                                    .and_then(|_| parse_hex4(s, i + 8))
+                                    .filter(|low| (0xDC00..=0xDFFF).contains(low));
+                                let len = if low.is_some() { 12 } else { 6 };
+                                (low.and_then(|low| char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))), len)
+                            }
+                            unit => (unit.and_then(char::from_u32), 6),
+                        };
+                        let c = c.ok_or_else(|| KeyPathParseError::new(InvalidEscape, offset + i, s[i..].chars().take(len).collect::<String>()))?;
+                        chars.nth(len - 3);
+                        c
+                    }
+                    _ => return Err(KeyPathParseError::new(InvalidEscape, offset + i, s[i..].chars().take(2).collect::<String>())),
+                };
+                result.push(escape);
+            }
+            c => result.push(c),
+        }
+    }
+    Err(KeyPathParseError::new(UnclosedQuote, offset, s))
+}
+
+fn parse_hex4(s: &str, start: usize) -> Option<u32> {
+    s.get(start..start + 4)
+        .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+}
@@ -0,0 +1,267 @@
+use core::fmt::{Display, Formatter};
+use serde_json::{Map, Value};
+use crate::{Item, KeyPath};
+
+/// Error returned when converting a `serde_json::Value` that isn't an array
+/// of string keys and non-negative integer indices into a `KeyPath`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JsonArrayError {
+    NotAnArray { value: Value },
+    InvalidItem { position: usize, value: Value },
+}
+
+impl Display for JsonArrayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        use JsonArrayError::*;
+        match self {
+            NotAnArray { value } => write!(f, "expected an array of keys and indices, found `{}`", value),
+            InvalidItem { position, value } => write!(f, "item `{}` at position {} is neither a string nor a non-negative integer", value, position),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for JsonArrayError { }
+
+impl KeyPath {
+
+    /// Converts the path to a JSON array like `["users", 3, "email"]`, the
+    /// shape of a GraphQL error path. `Item::NextIndex` becomes `"-"`.
+    pub fn to_json_array(&self) -> Value {
+        Value::Array(self.iter().map(|item| match item {
+            Item::Key(key) => Value::String(key.to_string()),
+            Item::Index(index) => Value::from(*index),
+            Item::NextIndex => Value::String("-".to_owned()),
+        }).collect())
+    }
+
+    /// Looks up the value this path points to. Keys index into objects and
+    /// indices into arrays; any other combination, a missing key or an index
+    /// out of range gives `None`. The empty path resolves to `value` itself.
+    pub fn resolve<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        self.iter().try_fold(value, |value, item| match (item, value) {
+            (Item::Key(key), Value::Object(map)) => map.get(key.as_ref()),
+            (Item::Index(index), Value::Array(values)) => values.get(*index),
+            _ => None,
+        })
+    }
+
+    /// Like `resolve`, but returns a mutable reference.
+    pub fn resolve_mut<'a>(&self, value: &'a mut Value) -> Option<&'a mut Value> {
+        self.iter().try_fold(value, |value, item| match (item, value) {
+            (Item::Key(key), Value::Object(map)) => map.get_mut(key.as_ref()),
+            (Item::Index(index), Value::Array(values)) => values.get_mut(*index),
+            _ => None,
+        })
+    }
+
+    /// Stores `new` at the location this path points to inside `root`.
+    ///
+    /// Missing keys are inserted, and a `null` reached by a key becomes an
+    /// empty object first, so a deep path can be set on a `null` root.
+    /// `Item::NextIndex` appends to an array. Indices past the end and items
+    /// that don't fit the type of an existing value are errors, in which case
+    /// the objects and array elements created so far are left in place.
+    pub fn set(&self, root: &mut Value, new: Value) -> Result<(), SetError> {
+        let mut node = root;
+        for (position, item) in self.iter().enumerate() {
+            if item.is_key() && node.is_null() {
+                *node = Value::Object(Map::new());
+            }
+            node = match (item, node) {
+                (Item::Key(key), Value::Object(map)) => map.entry(key.to_string()).or_insert(Value::Null),
+                (Item::Index(index), Value::Array(values)) => {
+                    let len = values.len();
+                    values.get_mut(*index).ok_or(SetError::IndexPastEnd { position, index: *index, len })?
+                }
+                (Item::NextIndex, Value::Array(values)) => {
+                    values.push(Value::Null);
+                    values.last_mut().expect("just pushed")
+                }
+                (_, node) => return Err(SetError::TypeMismatch { position, found: type_name(node) }),
+            };
+        }
+        *node = new;
+        Ok(())
+    }
+}
+
+/// Error returned by `KeyPath::set`, identifying the offending item by its
+/// position in the path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SetError {
+    /// A key was applied to something other than an object or `null`, or an
+    /// index to something other than an array. `found` is the JSON type of
+    /// the value, like `"array"`.
+    TypeMismatch { position: usize, found: &'static str },
+    IndexPastEnd { position: usize, index: usize, len: usize },
+}
+
+impl Display for SetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        use SetError::*;
+        match self {
+            TypeMismatch { position, found } => write!(f, "item at position {} can't be applied to {}", position, found),
+            IndexPastEnd { position, index, len } => write!(f, "index {} at position {} is past the end of an array of length {}", index, position, len),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SetError { }
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+impl TryFrom<&Value> for KeyPath {
+    type Error = JsonArrayError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let Value::Array(values) = value else {
+            return Err(JsonArrayError::NotAnArray { value: value.clone() });
+        };
+        values.iter().enumerate().map(|(position, value)| match value {
+            Value::String(key) => Ok(Item::from(key)),
+            Value::Number(n) => n.as_u64().and_then(|n| usize::try_from(n).ok()).map(Item::Index)
+                .ok_or_else(|| JsonArrayError::InvalidItem { position, value: value.clone() }),
+            _ => Err(JsonArrayError::InvalidItem { position, value: value.clone() }),
+        }).collect::<Result<Vec<Item>, _>>().map(KeyPath::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+    use crate::path;
+
+    #[test]
+    fn to_json_array_works() {
+        assert_eq!(path!["users", 3, "email"].to_json_array(), json!(["users", 3, "email"]));
+        assert_eq!(KeyPath::default().to_json_array(), json!([]));
+    }
+
+    #[test]
+    fn try_from_json_value_works() {
+        assert_eq!(KeyPath::try_from(&json!(["users", 3, "email"])), Ok(path!["users", 3, "email"]));
+        assert_eq!(KeyPath::try_from(&json!(["3"])), Ok(path!["3"]));
+    }
+
+    #[test]
+    fn json_array_round_trips() {
+        let path = path!["a.b", 0, "", "ü"];
+        assert_eq!(KeyPath::try_from(&path.to_json_array()), Ok(path));
+    }
+
+    #[test]
+    fn try_from_json_value_rejects_invalid_items() {
+        assert_eq!(KeyPath::try_from(&json!(["a", 1.5])), Err(JsonArrayError::InvalidItem { position: 1, value: json!(1.5) }));
+        assert_eq!(KeyPath::try_from(&json!([-1])), Err(JsonArrayError::InvalidItem { position: 0, value: json!(-1) }));
+        assert_eq!(KeyPath::try_from(&json!(["a", ["b"]])), Err(JsonArrayError::InvalidItem { position: 1, value: json!(["b"]) }));
+        assert_eq!(KeyPath::try_from(&json!([null])), Err(JsonArrayError::InvalidItem { position: 0, value: json!(null) }));
+        assert_eq!(KeyPath::try_from(&json!("a.b")), Err(JsonArrayError::NotAnArray { value: json!("a.b") }));
+    }
+
+    fn document() -> Value {
+        json!({
+            "users": [
+                { "name": "a", "tags": ["x", "y"] },
+                { "name": "b", "0": "zero" },
+            ],
+            "count": 2,
+        })
+    }
+
+    #[test]
+    fn resolve_works() {
+        let document = document();
+        assert_eq!(path!["users", 0, "name"].resolve(&document), Some(&json!("a")));
+        assert_eq!(path!["users", 0, "tags", 1].resolve(&document), Some(&json!("y")));
+        assert_eq!(path!["users", 1, "0"].resolve(&document), Some(&json!("zero")));
+        assert_eq!(path!["count"].resolve(&document), Some(&json!(2)));
+        assert_eq!(KeyPath::default().resolve(&document), Some(&document));
+    }
+
+    #[test]
+    fn resolve_returns_none_for_missing_values() {
+        let document = document();
+        assert_eq!(path!["missing"].resolve(&document), None);
+        assert_eq!(path!["users", 2].resolve(&document), None);
+        assert_eq!(path!["count", "x"].resolve(&document), None);
+    }
+
+    #[test]
+    fn resolve_returns_none_on_type_mismatch() {
+        let document = document();
+        assert_eq!(path!["users", "0"].resolve(&document), None);
+        assert_eq!(path!["users", 1, 0].resolve(&document), None);
+        assert_eq!(path![0].resolve(&document), None);
+        assert_eq!(KeyPath::new(vec![Item::Key("users".into()), Item::NextIndex]).resolve(&document), None);
+    }
+
+    #[test]
+    fn resolve_mut_works() {
+        let mut document = document();
+        *path!["users", 1, "name"].resolve_mut(&mut document).unwrap() = json!("c");
+        assert_eq!(path!["users", 1, "name"].resolve(&document), Some(&json!("c")));
+        assert_eq!(path!["users", "1"].resolve_mut(&mut document), None);
+        assert_eq!(KeyPath::default().resolve_mut(&mut document).cloned(), Some(document.clone()));
+    }
+
+    #[test]
+    fn set_creates_intermediate_objects() {
+        let mut root = Value::Null;
+        path!["database", "pool", "max_size"].set(&mut root, json!(10)).unwrap();
+        assert_eq!(root, json!({ "database": { "pool": { "max_size": 10 } } }));
+        path!["database", "host"].set(&mut root, json!("localhost")).unwrap();
+        assert_eq!(root, json!({ "database": { "pool": { "max_size": 10 }, "host": "localhost" } }));
+    }
+
+    #[test]
+    fn set_works_for_arrays() {
+        let mut document = document();
+        path!["users", 0, "tags", 0].set(&mut document, json!("z")).unwrap();
+        assert_eq!(path!["users", 0, "tags"].resolve(&document), Some(&json!(["z", "y"])));
+        KeyPath::new(vec![Item::Key("users".into()), Item::NextIndex, Item::Key("name".into())]).set(&mut document, json!("c")).unwrap();
+        assert_eq!(path!["users", 2].resolve(&document), Some(&json!({ "name": "c" })));
+    }
+
+    #[test]
+    fn set_replaces_the_root_for_the_empty_path() {
+        let mut root = json!({ "a": 1 });
+        KeyPath::default().set(&mut root, json!([1])).unwrap();
+        assert_eq!(root, json!([1]));
+    }
+
+    #[test]
+    fn set_errors_on_conflicting_types() {
+        let mut document = document();
+        assert_eq!(path!["users", "name"].set(&mut document, json!(1)), Err(SetError::TypeMismatch { position: 1, found: "an array" }));
+        assert_eq!(path![0].set(&mut document, json!(1)), Err(SetError::TypeMismatch { position: 0, found: "an object" }));
+        assert_eq!(path!["count", "x"].set(&mut document, json!(1)), Err(SetError::TypeMismatch { position: 1, found: "a number" }));
+        assert_eq!(path![0].set(&mut Value::Null, json!(1)), Err(SetError::TypeMismatch { position: 0, found: "null" }));
+        assert_eq!(document, self::document());
+    }
+
+    #[test]
+    fn set_errors_on_index_past_end() {
+        let mut document = document();
+        let error = path!["users", 2, "name"].set(&mut document, json!("c")).unwrap_err();
+        assert_eq!(error, SetError::IndexPastEnd { position: 1, index: 2, len: 2 });
+        assert_eq!(error.to_string(), "index 2 at position 1 is past the end of an array of length 2");
+    }
+
+    #[test]
+    fn json_array_error_display_works() {
+        let error = KeyPath::try_from(&json!(["a", -2])).unwrap_err();
+        assert_eq!(error.to_string(), "item `-2` at position 1 is neither a string nor a non-negative integer");
+    }
+}
@@ -0,0 +1,289 @@
+use alloc::borrow::{Cow, ToOwned};
+use alloc::string::String;
+use core::hash::{Hash, Hasher};
+use crate::{Item, KeyPath, KeyPathParseError, KeyPathParseErrorKind::*};
+
+/// A borrowed view over a path in `FromStr` syntax that yields its items
+/// without building a `KeyPath`.
+///
+/// The string is validated once by `new`. Keys borrow from it unless they
+/// contain escapes, in which case only that key is unescaped into a `String`.
+#[derive(Clone, Copy, Debug)]
+pub struct KeyPathStr<'a> {
+    source: &'a str,
+}
+
+/// An item yielded by `KeyPathStr`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ItemRef<'a> {
+    Key(Cow<'a, str>),
+    Index(usize),
+    NextIndex,
+}
+
+impl ItemRef<'_> {
+
+    pub fn to_item(&self) -> Item {
+        match self {
+            ItemRef::Key(key) => Item::Key(Cow::Owned(key.as_ref().to_owned())),
+            ItemRef::Index(index) => Item::Index(*index),
+            ItemRef::NextIndex => Item::NextIndex,
+        }
+    }
+}
+
+impl PartialEq<Item> for ItemRef<'_> {
+    fn eq(&self, other: &Item) -> bool {
+        match (self, other) {
+            (ItemRef::Key(a), Item::Key(b)) => a == b,
+            (ItemRef::Index(a), Item::Index(b)) => a == b,
+            (ItemRef::NextIndex, Item::NextIndex) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<'a> KeyPathStr<'a> {
+
+    pub fn new(source: &'a str) -> Result<Self, KeyPathParseError> {
+        for (offset, segment, escaped) in (Segments { source, pos: 0, done: source.is_empty() }) {
+            if segment.is_empty() {
+                return Err(KeyPathParseError::new(EmptySegment, offset, ""));
+            }
+            if !escaped && segment.bytes().all(|b| b.is_ascii_digit()) && segment.parse::<usize>().is_err() {
+                return Err(KeyPathParseError::new(IndexOverflow, offset, segment));
+            }
+        }
+        if source.bytes().rev().take_while(|b| *b == b'\\').count() % 2 == 1 {
+            return Err(KeyPathParseError::new(InvalidEscape, source.len() - 1, "\\"));
+        }
+        Ok(Self { source })
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        self.source
+    }
+
+    pub fn iter(&self) -> KeyPathStrIter<'a> {
+        KeyPathStrIter { segments: Segments { source: self.source, pos: 0, done: self.source.is_empty() } }
+    }
+
+    /// Counts the items, which takes a pass over the string.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.source.is_empty()
+    }
+
+    pub fn starts_with(&self, prefix: &KeyPath) -> bool {
+        let mut items = self.iter();
+        prefix.iter().all(|p| items.next().is_some_and(|i| i == *p))
+    }
+
+    pub fn to_key_path(&self) -> KeyPath {
+        KeyPath::new(self.iter().map(|i| i.to_item()).collect())
+    }
+}
+
+/// Compares the yielded items rather than the source text, so `a\b` equals
+/// `ab`.
+impl PartialEq for KeyPathStr<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source || self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for KeyPathStr<'_> { }
+
+/// Hashes the yielded items, consistent with `PartialEq`.
+impl Hash for KeyPathStr<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut len = 0;
+        for item in self.iter() {
+            item.hash(state);
+            len += 1;
+        }
+        state.write_usize(len);
+    }
+}
+
+impl PartialEq<KeyPath> for KeyPathStr<'_> {
+    fn eq(&self, other: &KeyPath) -> bool {
+        let mut items = self.iter();
+        other.iter().all(|o| items.next().is_some_and(|i| i == *o)) && items.next().is_none()
+    }
+}
+
+impl PartialEq<KeyPathStr<'_>> for KeyPath {
+    fn eq(&self, other: &KeyPathStr<'_>) -> bool {
+        other == self
+    }
+}
+
+impl<'a> IntoIterator for KeyPathStr<'a> {
+    type Item = ItemRef<'a>;
+    type IntoIter = KeyPathStrIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct KeyPathStrIter<'a> {
+    segments: Segments<'a>,
+}
+
+impl<'a> Iterator for KeyPathStrIter<'a> {
+    type Item = ItemRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (_, segment, escaped) = self.segments.next()?;
+        Some(if escaped {
+            ItemRef::Key(Cow::Owned(unescape(segment)))
+        } else if segment.bytes().all(|b| b.is_ascii_digit()) {
+            ItemRef::Index(segment.parse().expect("validated by KeyPathStr::new"))
+        } else if segment == "-" {
+            ItemRef::NextIndex
+        } else {
+            ItemRef::Key(Cow::Borrowed(segment))
+        })
+    }
+}
+
+/// Splits on unescaped dots, yielding each raw segment with its offset and
+/// whether it contains an escape.
+struct Segments<'a> {
+    source: &'a str,
+    pos: usize,
+    done: bool,
+}
+
+impl<'a> Iterator for Segments<'a> {
+    type Item = (usize, &'a str, bool);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let bytes = self.source.as_bytes();
+        let start = self.pos;
+        let mut escaped = false;
+        let mut i = start;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\\' => {
+                    escaped = true;
+                    i += 2;
+                }
+                b'.' => {
+                    self.pos = i + 1;
+                    return Some((start, &self.source[start..i], escaped));
+                }
+                _ => i += 1,
+            }
+        }
+        self.done = true;
+        Some((start, &self.source[start..], escaped))
+    }
+}
+
+fn unescape(segment: &str) -> String {
+    let mut result = String::with_capacity(segment.len());
+    let mut chars = segment.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => result.extend(chars.next()),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path;
+
+    #[test]
+    fn iter_works() {
+        let path = KeyPathStr::new("where.items.5.name").unwrap();
+        let items: Vec<ItemRef> = path.iter().collect();
+        assert_eq!(items, vec![
+            ItemRef::Key(Cow::Borrowed("where")),
+            ItemRef::Key(Cow::Borrowed("items")),
+            ItemRef::Index(5),
+            ItemRef::Key(Cow::Borrowed("name")),
+        ]);
+    }
+
+    #[test]
+    fn iter_borrows_unescaped_keys() {
+        let source = r"plain.esc\.aped.\5";
+        let path = KeyPathStr::new(source).unwrap();
+        let items: Vec<ItemRef> = path.iter().collect();
+        assert!(matches!(&items[0], ItemRef::Key(Cow::Borrowed(key)) if key.as_ptr() == source.as_ptr()));
+        assert_eq!(items[1], ItemRef::Key(Cow::Owned("esc.aped".to_owned())));
+        assert_eq!(items[2], ItemRef::Key(Cow::Owned("5".to_owned())));
+    }
+
+    #[test]
+    fn len_works() {
+        assert_eq!(KeyPathStr::new(r"a.b\.c.1").unwrap().len(), 3);
+        assert_eq!(KeyPathStr::new("").unwrap().len(), 0);
+        assert!(KeyPathStr::new("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn new_validates_like_from_str() {
+        for s in ["a..b", "a.", ".a", r"a\", r"a\\\", "a.99999999999999999999999"] {
+            assert_eq!(KeyPathStr::new(s).unwrap_err(), s.parse::<KeyPath>().unwrap_err());
+        }
+        assert!(KeyPathStr::new(r"a\\").is_ok());
+    }
+
+    #[test]
+    fn eq_works() {
+        let path = KeyPathStr::new(r"a.b\.c.1").unwrap();
+        assert_eq!(path, path!["a", "b.c", 1]);
+        assert_eq!(path!["a", "b.c", 1], path);
+        assert_ne!(path, path!["a", "b.c"]);
+        assert_ne!(path, path!["a", "b.c", 1, 2]);
+        assert_ne!(path, path!["a", "b.c", "1"]);
+    }
+
+    #[test]
+    fn eq_compares_items() {
+        assert_eq!(KeyPathStr::new(r"a\b").unwrap(), KeyPathStr::new("ab").unwrap());
+        assert_eq!(KeyPathStr::new(r"\x.1").unwrap(), KeyPathStr::new("x.1").unwrap());
+        assert_ne!(KeyPathStr::new(r"\1").unwrap(), KeyPathStr::new("1").unwrap());
+        assert_ne!(KeyPathStr::new("a.b").unwrap(), KeyPathStr::new("a").unwrap());
+    }
+
+    #[test]
+    fn hash_matches_eq() {
+        use std::collections::hash_map::DefaultHasher;
+        fn hash(path: KeyPathStr<'_>) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            path.hash(&mut hasher);
+            hasher.finish()
+        }
+        assert_eq!(hash(KeyPathStr::new(r"a\b.c").unwrap()), hash(KeyPathStr::new("ab.c").unwrap()));
+    }
+
+    #[test]
+    fn starts_with_works() {
+        let path = KeyPathStr::new("users.3.email").unwrap();
+        assert!(path.starts_with(&path!["users", 3]));
+        assert!(path.starts_with(&KeyPath::default()));
+        assert!(!path.starts_with(&path!["users", "3"]));
+        assert!(!path.starts_with(&path!["users", 3, "email", "x"]));
+    }
+
+    #[test]
+    fn to_key_path_works() {
+        let source = r"a.b\\c.1.-.\-";
+        assert_eq!(KeyPathStr::new(source).unwrap().to_key_path(), source.parse::<KeyPath>().unwrap());
+    }
+}
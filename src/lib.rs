@@ -1,54 +1,163 @@
-use core::fmt::{Display, Formatter};
-use std::ops::{Add, Index, Range};
+// Without the default `std` feature only `alloc` is needed. Tests always link
+// `std`, so `cargo build --no-default-features` is what checks the no_std build.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+use alloc::borrow::Cow;
+use core::borrow::Borrow;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::iter::FusedIterator;
+use core::ops::{Add, AddAssign, Deref, DerefMut, Index, Range, RangeBounds, RangeFrom, RangeFull, RangeInclusive, RangeTo};
+
+mod parse;
+mod bracket;
+mod canonical;
+mod case;
+mod caret;
+mod diff;
+mod env_var;
+pub mod field_mask;
+mod fingerprint;
+mod form;
+mod jq;
+mod json_path;
+mod json_pointer;
+#[cfg(feature = "serde_json")]
+mod json_value;
+mod json_string;
+mod key_path_str;
+mod mongo;
+mod normalize;
+mod pattern;
+#[cfg(feature = "proptest")]
+mod proptest_impl;
+mod relative;
+#[cfg(feature = "serde")]
+mod serde_impl;
+#[cfg(feature = "serde_path_to_error")]
+mod serde_path;
+mod xpath;
+
+pub use parse::{KeyPathParseError, KeyPathParseErrorKind, ParseOptions};
+pub use diff::PathDiff;
+pub use env_var::EnvVarError;
+pub use form::{EmptyBrackets, FormNameError};
+pub use jq::JqError;
+pub use json_pointer::JsonPointerError;
+pub use key_path_str::{ItemRef, KeyPathStr, KeyPathStrIter};
+pub use mongo::MongoKeyError;
+pub use normalize::AboveRootError;
+pub use pattern::{Pattern, PatternSegment};
+#[cfg(feature = "proptest")]
+pub use proptest_impl::KeyPathParameters;
+pub use relative::{NotAPrefixError, RelativeKeyPath, RelativePathError};
+pub use xpath::XPathError;
+#[cfg(feature = "serde_json")]
+pub use json_value::{JsonArrayError, SetError};
+
+/// Items are ordered by variant first: every `Key` sorts before every `Index`,
+/// and `NextIndex` sorts last. Keys compare as strings and indices numerically.
+///
+/// Keys built from string literals with `Item::key` or `path!` borrow them
+/// instead of allocating.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Item {
-    Key(String),
+    Key(Cow<'static, str>),
     Index(usize),
+    /// The position just past the end of an array, written `-` in JSON Pointer
+    /// and JSON Patch. It is neither a key nor a concrete index. With the
+    /// `serde` feature it serializes as `"-"`, so a `Key("-")` deserializes
+    /// back as `NextIndex`.
+    NextIndex,
 }
 
+/// Honors width, fill, alignment and precision like `str` does, indices
+/// included. The alternate flag `{:#}` wraps indices in brackets, as in `[5]`.
 impl Display for Item {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
-            Item::Key(s) => f.write_str(s.as_ref()),
-            Item::Index(n) => f.write_str(&n.to_string()),
+            Item::Key(s) => f.pad(s.as_ref()),
+            Item::Index(n) if f.alternate() => f.pad(&format!("[{}]", n)),
+            Item::Index(n) if needs_padding(f) => f.pad(&n.to_string()),
+            Item::NextIndex if f.alternate() => f.pad("[-]"),
+            Item::Index(n) => write!(f, "{}", n),
+            Item::NextIndex => f.pad("-"),
         }
     }
 }
 
+fn needs_padding(f: &Formatter<'_>) -> bool {
+    f.width().is_some() || f.precision().is_some()
+}
+
 impl Item {
 
+    /// Makes a key. A `&'static str`, like a literal, is borrowed rather than
+    /// copied.
+    pub fn key<K>(key: K) -> Self where K: Into<Cow<'static, str>> {
+        Item::Key(key.into())
+    }
+
     pub fn is_key(&self) -> bool {
         use Item::*;
         match self {
             Key(_) => true,
-            Index(_) => false,
+            Index(_) | NextIndex => false,
         }
     }
 
     pub fn is_index(&self) -> bool {
         use Item::*;
         match self {
-            Key(_) => false,
+            Key(_) | NextIndex => false,
             Index(_) => true,
         }
     }
 
+    pub fn is_next_index(&self) -> bool {
+        use Item::*;
+        match self {
+            Key(_) | Index(_) => false,
+            NextIndex => true,
+        }
+    }
+
     pub fn as_key(&self) -> Option<&str> {
         use Item::*;
         match self {
             Key(v) => Some(v.as_ref()),
-            Index(_) => None,
+            Index(_) | NextIndex => None,
         }
     }
 
     pub fn as_index(&self) -> Option<usize> {
         use Item::*;
         match self {
-            Key(_) => None,
+            Key(_) | NextIndex => None,
             Index(v) => Some(*v),
         }
     }
+
+    /// Turns a borrowed key into an owned one first.
+    pub fn as_key_mut(&mut self) -> Option<&mut String> {
+        use Item::*;
+        match self {
+            Key(v) => Some(v.to_mut()),
+            Index(_) | NextIndex => None,
+        }
+    }
+
+    pub fn as_index_mut(&mut self) -> Option<&mut usize> {
+        use Item::*;
+        match self {
+            Key(_) | NextIndex => None,
+            Index(v) => Some(v),
+        }
+    }
 }
 
 impl From<usize> for Item {
@@ -58,328 +167,2474 @@ impl From<usize> for Item {
     }
 }
 
-impl From<&str> for Item {
-    fn from(key: &str) -> Self {
+/// Error returned when converting an integer that doesn't fit in `usize`,
+/// such as a negative one, into an `Item`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IndexConversionError {
+    value: i128,
+}
+
+impl IndexConversionError {
+
+    pub fn value(&self) -> i128 {
+        self.value
+    }
+}
+
+impl Display for IndexConversionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "`{}` is not a valid index", self.value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for IndexConversionError { }
+
+// Only `usize` converts with `From`. Another `From` impl for an integer type
+// would leave literals like the ones in `path![1, 2]` and `path + 3` without a
+// single type to infer, so the other integer types convert with `TryFrom`.
+macro_rules! impl_try_from_int {
+    ($($t:ty),*) => {
+        $(
+            impl TryFrom<$t> for Item {
+                type Error = IndexConversionError;
+
+                fn try_from(index: $t) -> Result<Self, Self::Error> {
+                    usize::try_from(index).map(Item::Index).map_err(|_| IndexConversionError { value: index as i128 })
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_int!(u8, u16, u32, u64, i8, i16, i32, i64, isize);
+
+/// Copies the key. Use `Item::key` to borrow a `&'static str` instead.
+impl<'a> From<&'a str> for Item {
+    fn from(key: &'a str) -> Self {
         use Item::*;
-        Key(String::from(key))
+        Key(Cow::Owned(String::from(key)))
     }
 }
 
 impl From<String> for Item {
     fn from(key: String) -> Self {
         use Item::*;
-        Key(String::from(key))
+        Key(Cow::Owned(key))
     }
 }
 
 impl From<&String> for Item {
     fn from(key: &String) -> Self {
         use Item::*;
-        Key(String::from(key.as_str()))
+        Key(Cow::Owned(key.clone()))
+    }
+}
+
+impl From<Cow<'static, str>> for Item {
+    fn from(key: Cow<'static, str>) -> Self {
+        use Item::*;
+        Key(key)
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// Paths compare lexicographically by item, so a path sorts right before its
+/// descendants.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct KeyPath {
-    items: Vec<Item>
+    items: Items
 }
 
+// With the `smallvec` feature paths of up to 4 items are stored inline.
+#[cfg(not(feature = "smallvec"))]
+type Items = Vec<Item>;
+#[cfg(feature = "smallvec")]
+type Items = smallvec::SmallVec<[Item; 4]>;
+
 impl KeyPath {
 
-    pub fn new(items: Vec<Item>) -> Self {
+    /// The empty path, like `KeyPath::default()` but usable in `const` items.
+    pub const fn empty() -> Self {
+        #[cfg(not(feature = "smallvec"))]
+        let items = Vec::new();
+        #[cfg(feature = "smallvec")]
+        let items = smallvec::SmallVec::new_const();
         Self { items }
     }
 
+    #[allow(clippy::useless_conversion)]
+    pub fn new(items: Vec<Item>) -> Self {
+        Self { items: Items::from(items) }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { items: Items::with_capacity(capacity) }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.items.capacity()
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.items.reserve(additional)
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.items.shrink_to_fit()
+    }
+
+    pub fn as_slice(&self) -> &[Item] {
+        &self.items
+    }
+
+    pub fn into_items(self) -> Vec<Item> {
+        #[cfg(not(feature = "smallvec"))]
+        let items = self.items;
+        #[cfg(feature = "smallvec")]
+        let items = self.items.into_vec();
+        items
+    }
+
     pub fn len(&self) -> usize {
         self.items.len()
     }
 
+    /// The number of items, the same as `len`.
+    pub fn depth(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn key_count(&self) -> usize {
+        self.items.iter().filter(|i| i.is_key()).count()
+    }
+
+    /// Counts `Item::Index` items. `Item::NextIndex` is counted by neither
+    /// this nor `key_count`.
+    pub fn index_count(&self) -> usize {
+        self.items.iter().filter(|i| i.is_index()).count()
+    }
+
     pub fn get(&self, index: usize) -> Option<&Item> {
         self.items.get(index)
     }
 
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Item> {
+        self.items.get_mut(index)
+    }
+
+    pub fn first(&self) -> Option<&Item> {
+        self.items.first()
+    }
+
     pub fn last(&self) -> Option<&Item> {
         self.items.last()
     }
 
+    pub fn first_mut(&mut self) -> Option<&mut Item> {
+        self.items.first_mut()
+    }
+
+    pub fn last_mut(&mut self) -> Option<&mut Item> {
+        self.items.last_mut()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.items.is_empty()
     }
 
-    pub fn iter(&self) -> KeyPathIter {
-        KeyPathIter { key_path: self, index: 0 }
+    pub fn iter(&self) -> KeyPathIter<'_> {
+        KeyPathIter { items: self.items.iter() }
     }
-}
 
-impl Default for KeyPath {
-    fn default() -> Self {
-        Self { items: vec![] }
+    pub fn iter_mut(&mut self) -> KeyPathIterMut<'_> {
+        KeyPathIterMut { items: self.items.iter_mut() }
     }
-}
 
-impl AsRef<KeyPath> for KeyPath {
-    fn as_ref(&self) -> &KeyPath {
-        &self
+    /// Iterates over overlapping runs of `size` items, like `slice::windows`.
+    /// Yields nothing if the path is shorter than `size`.
+    ///
+    /// Panics if `size` is 0.
+    pub fn windows(&self, size: usize) -> impl Iterator<Item = &[Item]> {
+        self.items.windows(size)
     }
-}
 
-impl Display for KeyPath {
-    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
-        let s = self.items.iter().map(|i| i.to_string()).collect::<Vec<String>>().join(".");
-        f.write_str(&s)
+    /// Iterates over runs of `size` items that don't overlap, like
+    /// `slice::chunks`. The last run is shorter if `size` doesn't divide the
+    /// length.
+    ///
+    /// Panics if `size` is 0.
+    pub fn chunks(&self, size: usize) -> impl Iterator<Item = &[Item]> {
+        self.items.chunks(size)
     }
-}
 
-impl From<KeyPath> for String {
-    fn from(value: KeyPath) -> Self {
-        value.to_string()
+    /// Iterates over the keys, skipping indices. Paths that differ only in
+    /// their indices have equal keys: `path.keys().eq(other.keys())`.
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &str> + FusedIterator {
+        self.items.iter().filter_map(Item::as_key)
     }
-}
 
-impl From<&KeyPath> for String {
-    fn from(value: &KeyPath) -> Self {
-        value.to_string()
+    /// Iterates over the indices, skipping keys and `Item::NextIndex`.
+    pub fn indices(&self) -> impl DoubleEndedIterator<Item = usize> + FusedIterator + '_ {
+        self.items.iter().filter_map(Item::as_index)
     }
-}
 
-impl<'a, T> Add<T> for &KeyPath where T: Into<Item> {
-    type Output = KeyPath;
+    /// The same as `indices`.
+    pub fn indexes(&self) -> impl DoubleEndedIterator<Item = usize> + FusedIterator + '_ {
+        self.indices()
+    }
 
-    fn add(self, rhs: T) -> Self::Output {
-        let mut items = self.items.clone();
-        items.push(rhs.into());
-        KeyPath { items }
+    /// Appends an item in place. Together with `pop` this lets a recursive
+    /// walker reuse one path for a whole traversal instead of cloning it at
+    /// every level.
+    pub fn push<T>(&mut self, item: T) where T: Into<Item> {
+        self.items.push(item.into())
     }
-}
 
-impl<'a, T> Add<T> for KeyPath where T: Into<Item> {
-    type Output = Self;
+    pub fn pop(&mut self) -> Option<Item> {
+        self.items.pop()
+    }
 
-    fn add(self, rhs: T) -> Self::Output {
-        (&self).add(rhs)
+    /// Inserts an item at the start. This moves every other item, so building
+    /// a deep path this way is quadratic; prefer `push` then `reverse`.
+    pub fn push_front<T>(&mut self, item: T) where T: Into<Item> {
+        self.items.insert(0, item.into())
     }
-}
 
-impl Index<usize> for KeyPath {
-    type Output = Item;
+    /// Inserts the items of `prefix` at the start, moving the existing items
+    /// once.
+    pub fn prepend<P>(&mut self, prefix: P) where P: AsRef<[Item]> {
+        let prefix = prefix.as_ref();
+        let mut items = Items::with_capacity(prefix.len() + self.len());
+        items.extend(prefix.iter().cloned());
+        items.append(&mut self.items);
+        self.items = items;
+    }
 
-    fn index(&self, index: usize) -> &Self::Output {
-        &self.items[index]
+    /// Panics if `index > len`.
+    pub fn insert<T>(&mut self, index: usize, item: T) where T: Into<Item> {
+        self.items.insert(index, item.into())
     }
-}
 
-impl Index<Range<usize>> for KeyPath {
-    type Output = [Item];
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Item {
+        self.items.remove(index)
+    }
 
-    fn index(&self, index: Range<usize>) -> &Self::Output {
-        &self.items[index]
+    /// Removes the items in `range` and returns them in order. Items are
+    /// removed even if the iterator isn't consumed.
+    ///
+    /// Panics if the range is out of bounds, like `Vec::drain`.
+    pub fn drain<R>(&mut self, range: R) -> impl DoubleEndedIterator<Item = Item> + ExactSizeIterator + '_ where R: RangeBounds<usize> {
+        self.items.drain(range)
     }
-}
 
-impl From<&[Item]> for KeyPath {
-    fn from(items: &[Item]) -> Self {
-        Self { items: items.to_vec() }
+    /// Panics if `a` or `b` is out of bounds.
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.items.swap(a, b)
     }
-}
 
-pub struct KeyPathIter<'a> {
-    key_path: &'a KeyPath,
-    index: usize,
-}
+    /// Removes the item at `index` and moves the last item into its place.
+    /// This doesn't preserve the order of items, but is O(1).
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn swap_remove(&mut self, index: usize) -> Item {
+        self.items.swap_remove(index)
+    }
 
-impl<'a> Iterator for KeyPathIter<'a> {
-    type Item = &'a Item;
+    pub fn try_remove(&mut self, index: usize) -> Option<Item> {
+        (index < self.len()).then(|| self.items.remove(index))
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let result = self.key_path.get(self.index);
-        self.index += 1;
-        result
+    /// Moves the items of `other` onto the end of this path.
+    pub fn append<P>(&mut self, other: P) where P: Into<KeyPath> {
+        self.items.append(&mut other.into().items)
     }
-}
 
-impl<'a> IntoIterator for &'a KeyPath {
-    type Item = &'a Item;
-    type IntoIter = KeyPathIter<'a>;
+    pub fn contains<T>(&self, item: T) -> bool where T: Into<Item> {
+        self.items.contains(&item.into())
+    }
 
-    fn into_iter(self) -> Self::IntoIter {
-        KeyPathIter { key_path: self, index: 0 }
+    /// Whether any item is the key `key`. An index never matches, so
+    /// `contains_key("3")` is false for `path![3]`.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.keys().any(|k| k == key)
     }
-}
 
-impl<'a> IntoIterator for KeyPath {
-    type Item = Item;
-    type IntoIter = <Vec<Item> as IntoIterator>::IntoIter;
+    /// Whether any item is the index `index`. A key never matches.
+    pub fn contains_index(&self, index: usize) -> bool {
+        self.indices().any(|i| i == index)
+    }
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.items.into_iter()
+    /// Returns the position of the first item equal to `item`.
+    pub fn position<T>(&self, item: T) -> Option<usize> where T: Into<Item> {
+        let item = item.into();
+        self.items.iter().position(|i| *i == item)
     }
-}
 
-#[macro_export]
-macro_rules! path {
-    (@single $($x:tt)*) => (());
-    (@count $($rest:expr),*) => (<[()]>::len(&[$(path!(@single $rest)),*]));
-    (@item $other: expr) => ($crate::Item::from($other));
-    ($($key:expr,)+) => { path!($($key),+) };
-    ($($key:expr),*) => {
-        {
-            let _cap = path!(@count $($key),*);
-            let mut _items = ::std::vec::Vec::with_capacity(_cap);
-            $(
-                let _ = _items.push(path!(@item $key));
-            )*
-            $crate::KeyPath::new(_items)
-        }
-    };
-}
+    pub fn reverse(&mut self) {
+        self.items.reverse()
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    pub fn reversed(&self) -> KeyPath {
+        self.items.iter().rev().cloned().collect()
+    }
 
-    #[test]
-    fn macro_works_for_empty() {
-        let result = path![];
-        assert_eq!(result, KeyPath::default());
+    /// Keeps only the items for which `f` returns `true`, so
+    /// `path.retain(Item::is_key)` drops every index.
+    pub fn retain<F>(&mut self, mut f: F) where F: FnMut(&Item) -> bool {
+        self.items.retain(|item| f(item))
     }
 
-    #[test]
-    fn macro_works_for_2_strings() {
-        let result = path!["a", "b"];
-        assert_eq!(result, KeyPath { items: vec![Item::Key("a".into()), Item::Key("b".into())]});
+    /// Like `retain`, but builds a new path.
+    pub fn filtered<F>(&self, mut f: F) -> KeyPath where F: FnMut(&Item) -> bool {
+        self.items.iter().filter(|item| f(item)).cloned().collect()
     }
 
-    #[test]
-    fn macro_works_for_2_numbers() {
-        let result = path![2, 5];
-        assert_eq!(result, KeyPath { items: vec![Item::Index(2), Item::Index(5)]});
+    /// Collapses runs of equal items into one, like `Vec::dedup`. Only
+    /// adjacent items are compared, so `a.b.a` is left as it is.
+    pub fn dedup(&mut self) {
+        self.items.dedup()
     }
 
-    #[test]
-    fn macro_works_for_2_mixed_items() {
-        let string = "where".to_owned();
-        let result = path![string, 5];
-        assert_eq!(result, KeyPath { items: vec![Item::Key("where".to_owned().into()), Item::Index(5)]});
+    /// Like `dedup`, but builds a new path.
+    pub fn deduped(&self) -> KeyPath {
+        let mut path = self.clone();
+        path.dedup();
+        path
     }
 
-    #[test]
-    fn macro_works_for_2_items_with_trailing_comma() {
-        let string = "where".to_owned();
-        let result = path![string, 5,];
-        assert_eq!(result, KeyPath { items: vec![Item::Key("where".to_owned().into()), Item::Index(5)]});
+    /// Builds a new path from the result of `f` on each item.
+    pub fn map<F>(&self, f: F) -> KeyPath where F: FnMut(&Item) -> Item {
+        self.items.iter().map(f).collect()
     }
 
-    #[test]
-    fn macro_works_for_3_items() {
-        let string = "where".to_owned();
-        let result = path![string, 5, 7];
-        assert_eq!(result, KeyPath { items: vec![Item::Key("where".to_owned().into()), Item::Index(5), Item::Index(7)]});
+    /// Like `map`, but consumes the path and passes the items by value, so
+    /// keys can be reused without cloning.
+    pub fn map_items<F>(self, f: F) -> KeyPath where F: FnMut(Item) -> Item {
+        self.items.into_iter().map(f).collect()
     }
 
-    #[test]
-    fn macro_works_for_3_items_with_trailing_comma() {
-        let string = "where".to_owned();
-        let result = path![string, 5, 7, ];
-        assert_eq!(result, KeyPath { items: vec![Item::Key("where".to_owned().into()), Item::Index(5), Item::Index(7)]});
+    pub fn truncate(&mut self, len: usize) {
+        self.items.truncate(len)
     }
 
-    #[test]
-    fn add_works_for_number() {
-        let path = KeyPath::default();
-        let result = path + 45;
-        assert_eq!(result, KeyPath { items: vec![Item::Index(45)] })
+    /// Returns the first `n` items, or a clone of the whole path if it is
+    /// shorter than that.
+    pub fn take(&self, n: usize) -> KeyPath {
+        KeyPath::from(&self.items[..n.min(self.len())])
     }
 
-    #[test]
-    fn add_works_for_str() {
-        let path = KeyPath::default();
-        let result = path + "";
-        assert_eq!(result, KeyPath { items: vec![Item::Key("".into())] })
+    /// The same as `take`.
+    pub fn prefix(&self, n: usize) -> KeyPath {
+        self.take(n)
     }
 
-    #[test]
-    fn add_works_for_string() {
-        let path = KeyPath::default();
-        let result = path + "a".to_owned();
-        assert_eq!(result, KeyPath { items: vec![Item::Key("a".to_owned().into())] })
+    /// Returns the last `n` items, or a clone of the whole path if it is
+    /// shorter than that.
+    pub fn suffix(&self, n: usize) -> KeyPath {
+        KeyPath::from(&self.items[self.len().saturating_sub(n)..])
     }
 
-    #[test]
-    fn add_works_for_string_ref() {
-        let path = KeyPath::default();
-        let string = "abc".to_owned();
-        let string_ref = &string;
-        let result = path + string_ref;
-        assert_eq!(result, KeyPath { items: vec![Item::Key("abc".into())] })
+    /// Splits the path into the items before `mid` and the rest.
+    ///
+    /// Panics if `mid > len`, like `slice::split_at`.
+    pub fn split_at(&self, mid: usize) -> (KeyPath, KeyPath) {
+        let (head, tail) = self.items.split_at(mid);
+        (KeyPath::from(head), KeyPath::from(tail))
     }
 
-    #[test]
-    fn key_path_can_be_debug_printed() {
-        let path = path!["where", "items", 5, "name"];
-        let result = format!("{}", path);
-        assert_eq!(&result, "where.items.5.name");
+    /// Like `split_at`, but moves the items instead of cloning them.
+    ///
+    /// Panics if `mid > len`.
+    pub fn into_split_at(mut self, mid: usize) -> (KeyPath, KeyPath) {
+        let tail = self.items.drain(mid..).collect();
+        (self, KeyPath { items: tail })
     }
 
-    #[test]
-    fn index_works() {
-        let path = path!["orderBy", "name"];
-        let result = &path[0];
-        assert_eq!(result, &("orderBy".into()))
+    pub fn split_first(&self) -> Option<(&Item, &[Item])> {
+        self.items.split_first()
     }
 
-    #[test]
-    fn index_with_range_works() {
-        let path = path!["orderBy", "name", 3, "good"];
-        let result = &path[0..2];
-        assert_eq!(result, &[Item::Key("orderBy".to_string()), Item::Key("name".to_string())])
+    pub fn split_last(&self) -> Option<(&Item, &[Item])> {
+        self.items.split_last()
     }
 
-    #[test]
-    fn get_works() {
-        let path = path!["orderBy", "name"];
-        let result = path.get(0).unwrap();
-        assert_eq!(result, &("orderBy".into()))
+    /// Like `split_first`, but consumes the path. This shifts the remaining
+    /// items, so to walk a whole path prefer `split_first` on slices.
+    pub fn into_split_first(mut self) -> Option<(Item, KeyPath)> {
+        if self.is_empty() {
+            return None;
+        }
+        let first = self.items.remove(0);
+        Some((first, self))
     }
 
-    #[test]
-    fn last_works() {
-        let path = path!["orderBy", "name"];
-        let result = path.last().unwrap();
-        assert_eq!(result, &("name".into()))
+    /// Like `split_last`, but consumes the path.
+    pub fn into_split_last(mut self) -> Option<(Item, KeyPath)> {
+        let last = self.items.pop()?;
+        Some((last, self))
     }
 
-    #[test]
-    fn as_ref_works() {
-        let path = path!["a", "b"];
-        let path2 = path.as_ref();
-        let path3 = path2.as_ref();
-        assert_eq!(&path, path2);
-        assert_eq!(path2, path3);
+    /// Returns the path without its last item, or `None` for the empty path.
+    pub fn parent(&self) -> Option<KeyPath> {
+        self.items.split_last().map(|(_, rest)| KeyPath::from(rest))
+    }
 
+    /// Iterates over the proper prefixes of the path as slices, longest
+    /// first and ending with the empty slice. The path itself is not
+    /// included, see `ancestors_inclusive`.
+    pub fn ancestors(&self) -> KeyPathAncestors<'_> {
+        KeyPathAncestors { items: &self.items, remaining: self.len() }
     }
 
-    #[test]
-    fn to_string_works() {
-        let path = path!["a", 2, "3"];
-        assert_eq!(&path.to_string(), "a.2.3");
+    /// Like `ancestors`, but starts with the whole path.
+    pub fn ancestors_inclusive(&self) -> KeyPathAncestors<'_> {
+        KeyPathAncestors { items: &self.items, remaining: self.len() + 1 }
     }
 
-    #[test]
-    fn ref_to_string_works() {
-        let path = path!["a", 2, "3"];
-        let path_ref = &path;
-        assert_eq!(&path_ref.to_string(), "a.2.3");
+    /// Accepts a `KeyPath` by value or reference, or a slice of items. The
+    /// empty path is a prefix of every path.
+    pub fn starts_with<P>(&self, prefix: P) -> bool where P: AsRef<[Item]> {
+        self.items.starts_with(prefix.as_ref())
     }
 
-    #[test]
-    fn iter_works() {
-        let path = path!["a", 2, "3"];
-        let path = &path;
-        let mut result = "".to_owned();
-        for item in path {
-            result += format!("{}", item).as_str();
-        }
-        assert_eq!(&result, "a23");
+    /// Accepts a `KeyPath` by value or reference, or a slice of items. The
+    /// empty path is a suffix of every path.
+    pub fn ends_with<P>(&self, suffix: P) -> bool where P: AsRef<[Item]> {
+        self.items.ends_with(suffix.as_ref())
+    }
+
+    /// Whether `self` is a strict prefix of `other`. A path is never its own
+    /// ancestor.
+    pub fn is_ancestor_of(&self, other: &KeyPath) -> bool {
+        self.len() < other.len() && other.starts_with(self)
+    }
+
+    /// Whether `other` is a strict prefix of `self`.
+    pub fn is_descendant_of(&self, other: &KeyPath) -> bool {
+        other.is_ancestor_of(self)
+    }
+
+    /// Returns the rest of the path after `prefix`, or `None` if it does not
+    /// start with `prefix` item for item.
+    pub fn strip_prefix<P>(&self, prefix: P) -> Option<KeyPath> where P: AsRef<[Item]> {
+        self.strip_prefix_slice(prefix).map(KeyPath::from)
+    }
+
+    /// Returns the path before `suffix`, or `None` if it does not end with
+    /// `suffix` item for item.
+    pub fn strip_suffix<P>(&self, suffix: P) -> Option<KeyPath> where P: AsRef<[Item]> {
+        self.strip_suffix_slice(suffix).map(KeyPath::from)
+    }
+
+    /// Like `strip_prefix`, but borrows the rest instead of cloning it.
+    pub fn strip_prefix_slice<P>(&self, prefix: P) -> Option<&[Item]> where P: AsRef<[Item]> {
+        self.items.strip_prefix(prefix.as_ref())
+    }
+
+    /// Like `strip_suffix`, but borrows the rest instead of cloning it.
+    pub fn strip_suffix_slice<P>(&self, suffix: P) -> Option<&[Item]> where P: AsRef<[Item]> {
+        self.items.strip_suffix(suffix.as_ref())
+    }
+
+    pub fn common_prefix(&self, other: &KeyPath) -> KeyPath {
+        KeyPath::from(&self.items[..self.common_prefix_len(other)])
+    }
+
+    /// Pairs up the items of both paths by position, stopping at the end of
+    /// the shorter one.
+    pub fn zip<'a>(&'a self, other: &'a KeyPath) -> impl DoubleEndedIterator<Item = (&'a Item, &'a Item)> + ExactSizeIterator {
+        self.items.iter().zip(other.items.iter())
+    }
+
+    /// The number of leading items shared with `other`, without allocating.
+    pub fn common_prefix_len(&self, other: &KeyPath) -> usize {
+        self.items.iter().zip(other.items.iter()).take_while(|(a, b)| a == b).count()
+    }
+
+    /// The longest prefix shared by all `paths`, or the empty path if there
+    /// are none.
+    pub fn common_prefix_of<'a, I>(paths: I) -> KeyPath where I: IntoIterator<Item = &'a KeyPath> {
+        let mut paths = paths.into_iter();
+        let Some(first) = paths.next() else {
+            return KeyPath::default();
+        };
+        let len = paths.fold(first.len(), |len, path| len.min(first.common_prefix_len(path)));
+        first.take(len)
+    }
+
+    /// The exact length in bytes of the `Display` output, without rendering it.
+    pub fn rendered_len(&self) -> usize {
+        let items: usize = self.items.iter().map(|item| match item {
+            Item::Key(key) => key.len() + key.bytes().filter(|b| *b == b'.' || *b == b'\\').count(),
+            Item::Index(index) => index.checked_ilog10().map_or(1, |digits| digits as usize + 1),
+            Item::NextIndex => 1,
+        }).sum();
+        items + self.len().saturating_sub(1)
+    }
+
+    /// Appends the `Display` output to `out`, reserving the space up front.
+    pub fn write_to(&self, out: &mut String) {
+        out.reserve(self.rendered_len());
+        write_escaped_path(out, &self.items).expect("writing to a String can't fail");
+    }
+
+    /// Joins the items with `.` without escaping, so keys containing `.` or
+    /// `\` can't be told apart from separators.
+    pub fn to_string_unescaped(&self) -> String {
+        self.join_with(".")
+    }
+
+    /// Joins the items with `sep` without escaping.
+    pub fn join_with(&self, sep: &str) -> String {
+        self.display_with(sep).to_string()
+    }
+
+    /// The same as `join_with`.
+    pub fn to_string_with_separator(&self, sep: &str) -> String {
+        self.join_with(sep)
+    }
+
+    /// Displays the items joined with `sep` without escaping, rendering only
+    /// when formatted.
+    pub fn display_with<'a>(&'a self, sep: &'a str) -> DisplayWith<'a> {
+        DisplayWith { items: &self.items, sep }
+    }
+}
+
+/// Returned by `KeyPath::display_with`.
+#[derive(Clone, Copy, Debug)]
+pub struct DisplayWith<'a> {
+    items: &'a [Item],
+    sep: &'a str,
+}
+
+impl Display for DisplayWith<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                f.write_str(self.sep)?;
+            }
+            write!(f, "{}", item)?;
+        }
+        Ok(())
+    }
+}
+
+impl AsRef<KeyPath> for KeyPath {
+    fn as_ref(&self) -> &KeyPath {
+        self
+    }
+}
+
+impl AsRef<[Item]> for KeyPath {
+    fn as_ref(&self) -> &[Item] {
+        &self.items
+    }
+}
+
+/// `Eq`, `Ord` and `Hash` on `KeyPath` are those of its items as a slice, so
+/// maps keyed by `KeyPath` can be looked up with an `&[Item]`.
+impl Borrow<[Item]> for KeyPath {
+    fn borrow(&self) -> &[Item] {
+        &self.items
+    }
+}
+
+/// Joins the items with `.`, backslash-escaping `.` and `\` inside keys.
+///
+/// The alternate flag `{:#}` renders the bracket form of `to_bracket_string`
+/// instead, as in `items[5].name`.
+///
+/// Width, fill, alignment and precision apply to the whole rendered path.
+/// Without them the output is written straight into the formatter.
+impl Display for KeyPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if f.alternate() {
+            f.pad(&self.to_bracket_string())
+        } else if needs_padding(f) {
+            let mut s = String::new();
+            write_escaped_path(&mut s, &self.items)?;
+            f.pad(&s)
+        } else {
+            write_escaped_path(f, &self.items)
+        }
+    }
+}
+
+pub(crate) fn write_escaped_path<W>(out: &mut W, items: &[Item]) -> core::fmt::Result where W: core::fmt::Write {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            out.write_char('.')?;
+        }
+        match item {
+            Item::Key(key) => write_escaped_key(out, key)?,
+            Item::Index(index) => write!(out, "{}", index)?,
+            Item::NextIndex => out.write_char('-')?,
+        }
+    }
+    Ok(())
+}
+
+/// Writes `key` with `.` and `\` backslash-escaped, copying the runs between
+/// them as whole slices. The key `-` is written `\-` so that it doesn't read
+/// back as `Item::NextIndex`.
+pub(crate) fn write_escaped_key<W>(out: &mut W, key: &str) -> core::fmt::Result where W: core::fmt::Write {
+    if key == "-" {
+        return out.write_str("\\-");
+    }
+    let mut start = 0;
+    for (i, c) in key.char_indices() {
+        if c == '.' || c == '\\' {
+            out.write_str(&key[start..i])?;
+            out.write_char('\\')?;
+            start = i;
+        }
+    }
+    out.write_str(&key[start..])
+}
+
+pub(crate) fn escape_key(key: &str) -> String {
+    let mut result = String::with_capacity(key.len());
+    write_escaped_key(&mut result, key).expect("writing to a String can't fail");
+    result
+}
+
+/// Compares against the `Display` output, so `path!["a.b"] == r"a\.b"`.
+impl PartialEq<str> for KeyPath {
+    fn eq(&self, other: &str) -> bool {
+        struct Matcher<'a>(&'a str);
+
+        impl core::fmt::Write for Matcher<'_> {
+            fn write_str(&mut self, s: &str) -> core::fmt::Result {
+                self.0 = self.0.strip_prefix(s).ok_or(core::fmt::Error)?;
+                Ok(())
+            }
+        }
+
+        let mut matcher = Matcher(other);
+        write_escaped_path(&mut matcher, &self.items).is_ok() && matcher.0.is_empty()
+    }
+}
+
+impl PartialEq<&str> for KeyPath {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl PartialEq<KeyPath> for str {
+    fn eq(&self, other: &KeyPath) -> bool {
+        other == self
+    }
+}
+
+impl PartialEq<KeyPath> for &str {
+    fn eq(&self, other: &KeyPath) -> bool {
+        other == *self
+    }
+}
+
+impl From<KeyPath> for String {
+    fn from(value: KeyPath) -> Self {
+        String::from(&value)
+    }
+}
+
+impl From<&KeyPath> for String {
+    fn from(value: &KeyPath) -> Self {
+        let mut result = String::new();
+        value.write_to(&mut result);
+        result
+    }
+}
+
+impl<T> Add<T> for &KeyPath where T: Into<Item> {
+    type Output = KeyPath;
+
+    fn add(self, rhs: T) -> Self::Output {
+        let mut items = Items::with_capacity(self.len() + 1);
+        items.extend(self.items.iter().cloned());
+        items.push(rhs.into());
+        KeyPath { items }
+    }
+}
+
+impl<T> Add<T> for KeyPath where T: Into<Item> {
+    type Output = Self;
+
+    fn add(mut self, rhs: T) -> Self::Output {
+        self.items.push(rhs.into());
+        self
+    }
+}
+
+impl Add<&KeyPath> for &KeyPath {
+    type Output = KeyPath;
+
+    fn add(self, rhs: &KeyPath) -> Self::Output {
+        let mut items = Items::with_capacity(self.len() + rhs.len());
+        items.extend(self.items.iter().cloned());
+        items.extend(rhs.items.iter().cloned());
+        KeyPath { items }
+    }
+}
+
+impl Add<KeyPath> for &KeyPath {
+    type Output = KeyPath;
+
+    fn add(self, rhs: KeyPath) -> Self::Output {
+        self.add(&rhs)
+    }
+}
+
+impl Add<&KeyPath> for KeyPath {
+    type Output = KeyPath;
+
+    fn add(mut self, rhs: &KeyPath) -> Self::Output {
+        self.items.extend(rhs.items.iter().cloned());
+        self
+    }
+}
+
+impl Add<KeyPath> for KeyPath {
+    type Output = KeyPath;
+
+    fn add(mut self, mut rhs: KeyPath) -> Self::Output {
+        self.items.append(&mut rhs.items);
+        self
+    }
+}
+
+impl<T> AddAssign<T> for KeyPath where T: Into<Item> {
+    fn add_assign(&mut self, rhs: T) {
+        self.items.push(rhs.into());
+    }
+}
+
+impl AddAssign<KeyPath> for KeyPath {
+    fn add_assign(&mut self, mut rhs: KeyPath) {
+        self.items.append(&mut rhs.items);
+    }
+}
+
+impl AddAssign<&KeyPath> for KeyPath {
+    fn add_assign(&mut self, rhs: &KeyPath) {
+        self.items.extend(rhs.items.iter().cloned());
+    }
+}
+
+/// Gives paths the methods of `[Item]`, like `String` has those of `str`.
+impl Deref for KeyPath {
+    type Target = [Item];
+
+    fn deref(&self) -> &Self::Target {
+        &self.items
+    }
+}
+
+/// A path has no invariants beyond those of its items, so they can be
+/// changed freely through slice methods like `sort` and `swap`.
+impl DerefMut for KeyPath {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.items
+    }
+}
+
+impl Index<usize> for KeyPath {
+    type Output = Item;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.items[index]
+    }
+}
+
+impl Index<Range<usize>> for KeyPath {
+    type Output = [Item];
+
+    fn index(&self, index: Range<usize>) -> &Self::Output {
+        &self.items[index]
+    }
+}
+
+impl Index<RangeFrom<usize>> for KeyPath {
+    type Output = [Item];
+
+    fn index(&self, index: RangeFrom<usize>) -> &Self::Output {
+        &self.items[index]
+    }
+}
+
+impl Index<RangeTo<usize>> for KeyPath {
+    type Output = [Item];
+
+    fn index(&self, index: RangeTo<usize>) -> &Self::Output {
+        &self.items[index]
+    }
+}
+
+impl Index<RangeFull> for KeyPath {
+    type Output = [Item];
+
+    fn index(&self, index: RangeFull) -> &Self::Output {
+        &self.items[index]
+    }
+}
+
+impl Index<RangeInclusive<usize>> for KeyPath {
+    type Output = [Item];
+
+    fn index(&self, index: RangeInclusive<usize>) -> &Self::Output {
+        &self.items[index]
+    }
+}
+
+impl From<&[Item]> for KeyPath {
+    fn from(items: &[Item]) -> Self {
+        Self { items: Items::from(items) }
+    }
+}
+
+impl From<KeyPath> for Vec<Item> {
+    fn from(path: KeyPath) -> Self {
+        path.into_items()
+    }
+}
+
+/// Converts each element with `Item::from`, so `KeyPath::from(["a", "b"])`
+/// is `path!["a", "b"]` and `KeyPath::from([1, 2])` is `path![1, 2]`.
+impl<T, const N: usize> From<[T; N]> for KeyPath where T: Into<Item> {
+    fn from(items: [T; N]) -> Self {
+        items.into_iter().collect()
+    }
+}
+
+/// Converts each element with `Item::from`, like `From<[T; N]>`.
+impl<T> From<Vec<T>> for KeyPath where T: Into<Item> {
+    fn from(items: Vec<T>) -> Self {
+        items.into_iter().collect()
+    }
+}
+
+impl<T> Extend<T> for KeyPath where T: Into<Item> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.items.reserve(iter.size_hint().0);
+        self.items.extend(iter.map(Into::into));
+    }
+}
+
+impl<T> FromIterator<T> for KeyPath where T: Into<Item> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut items = Items::with_capacity(iter.size_hint().0);
+        items.extend(iter.map(Into::into));
+        Self { items }
+    }
+}
+
+pub struct KeyPathIter<'a> {
+    items: core::slice::Iter<'a, Item>,
+}
+
+impl<'a> Iterator for KeyPathIter<'a> {
+    type Item = &'a Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.items.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.items.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for KeyPathIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.items.next_back()
+    }
+}
+
+impl ExactSizeIterator for KeyPathIter<'_> { }
+
+pub struct KeyPathIterMut<'a> {
+    items: core::slice::IterMut<'a, Item>,
+}
+
+impl<'a> Iterator for KeyPathIterMut<'a> {
+    type Item = &'a mut Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.items.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.items.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for KeyPathIterMut<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.items.next_back()
+    }
+}
+
+impl ExactSizeIterator for KeyPathIterMut<'_> { }
+
+impl<'a> IntoIterator for &'a mut KeyPath {
+    type Item = &'a mut Item;
+    type IntoIter = KeyPathIterMut<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Iterator returned by `KeyPath::ancestors` and
+/// `KeyPath::ancestors_inclusive`.
+pub struct KeyPathAncestors<'a> {
+    items: &'a [Item],
+    remaining: usize,
+}
+
+impl<'a> Iterator for KeyPathAncestors<'a> {
+    type Item = &'a [Item];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.remaining = self.remaining.checked_sub(1)?;
+        Some(&self.items[..self.remaining])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for KeyPathAncestors<'_> { }
+
+impl<'a> IntoIterator for &'a KeyPath {
+    type Item = &'a Item;
+    type IntoIter = KeyPathIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        KeyPathIter { items: self.items.iter() }
+    }
+}
+
+pub struct KeyPathIntoIter {
+    items: <Items as IntoIterator>::IntoIter,
+}
+
+impl Iterator for KeyPathIntoIter {
+    type Item = Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.items.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.items.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for KeyPathIntoIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.items.next_back()
+    }
+}
+
+impl ExactSizeIterator for KeyPathIntoIter { }
+
+impl IntoIterator for KeyPath {
+    type Item = Item;
+    type IntoIter = KeyPathIntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        KeyPathIntoIter { items: self.items.into_iter() }
+    }
+}
+
+#[macro_export]
+macro_rules! path {
+    (@single $($x:tt)*) => (());
+    (@count $($rest:expr),*) => (<[()]>::len(&[$(path!(@single $rest)),*]));
+    // Literals are matched apart from other expressions so that string
+    // literals can be borrowed.
+    (@push $items:ident) => ();
+    (@push $items:ident $key:literal $(, $($rest:tt)*)?) => {
+        $items.push($crate::__private::FromLiteral::into_item($key));
+        path!(@push $items $($($rest)*)?);
+    };
+    (@push $items:ident $key:expr $(, $($rest:tt)*)?) => {
+        $items.push($crate::Item::from($key));
+        path!(@push $items $($($rest)*)?);
+    };
+    ($($key:expr,)+) => { path!($($key),+) };
+    ($($key:tt)*) => {
+        {
+            let _cap = path!(@count $($key)*);
+            #[allow(unused_mut)]
+            let mut _items = $crate::__private::Vec::with_capacity(_cap);
+            path!(@push _items $($key)*);
+            $crate::KeyPath::new(_items)
+        }
+    };
+}
+
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::vec::Vec;
+    use crate::Item;
+
+    pub trait FromLiteral {
+        fn into_item(self) -> Item;
+    }
+
+    impl FromLiteral for &'static str {
+        fn into_item(self) -> Item {
+            Item::key(self)
+        }
+    }
+
+    impl FromLiteral for usize {
+        fn into_item(self) -> Item {
+            Item::Index(self)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "std"))]
+    #[test]
+    fn works_without_std() {
+        let path: KeyPath = "a.b\\.c.1".parse().unwrap();
+        assert_eq!(path, path!["a", "b.c", 1]);
+        assert_eq!(path.to_string(), alloc::string::String::from("a.b\\.c.1"));
+        assert_eq!(KeyPath::parse("a..b").unwrap_err().kind(), KeyPathParseErrorKind::EmptySegment);
+    }
+
+    #[cfg(feature = "smallvec")]
+    #[test]
+    fn smallvec_storage_matches_vec_construction() {
+        let short = path!["a", "b", 1];
+        assert!(!short.items.spilled());
+        assert_eq!(short, KeyPath::new(vec![Item::Key("a".into()), Item::Key("b".into()), Item::Index(1)]));
+        let long: KeyPath = (0..8).collect();
+        assert!(long.items.spilled());
+        assert_eq!(long, KeyPath::new((0..8).map(Item::Index).collect()));
+        assert_eq!(long.iter().len(), 8);
+        assert_eq!(long[7], Item::Index(7));
+    }
+
+    #[test]
+    fn macro_works_for_empty() {
+        let result = path![];
+        assert_eq!(result, KeyPath::default());
+    }
+
+    #[test]
+    fn macro_works_for_2_strings() {
+        let result = path!["a", "b"];
+        assert_eq!(result, KeyPath::new(vec![Item::Key("a".into()), Item::Key("b".into())]));
+    }
+
+    #[test]
+    fn macro_works_for_2_numbers() {
+        let result = path![2, 5];
+        assert_eq!(result, KeyPath::new(vec![Item::Index(2), Item::Index(5)]));
+    }
+
+    #[test]
+    fn macro_works_for_2_mixed_items() {
+        let string = "where".to_owned();
+        let result = path![string, 5];
+        assert_eq!(result, KeyPath::new(vec![Item::Key("where".into()), Item::Index(5)]));
+    }
+
+    #[test]
+    fn macro_works_for_2_items_with_trailing_comma() {
+        let string = "where".to_owned();
+        let result = path![string, 5,];
+        assert_eq!(result, KeyPath::new(vec![Item::Key("where".into()), Item::Index(5)]));
+    }
+
+    #[test]
+    fn macro_works_for_3_items() {
+        let string = "where".to_owned();
+        let result = path![string, 5, 7];
+        assert_eq!(result, KeyPath::new(vec![Item::Key("where".into()), Item::Index(5), Item::Index(7)]));
+    }
+
+    #[test]
+    fn macro_works_for_3_items_with_trailing_comma() {
+        let string = "where".to_owned();
+        let result = path![string, 5, 7, ];
+        assert_eq!(result, KeyPath::new(vec![Item::Key("where".into()), Item::Index(5), Item::Index(7)]));
+    }
+
+    #[test]
+    fn try_from_works_for_integers() {
+        assert_eq!(Item::try_from(5u32), Ok(Item::Index(5)));
+        assert_eq!(Item::try_from(5u8), Ok(Item::Index(5)));
+        assert_eq!(Item::try_from(7u64), Ok(Item::Index(7)));
+        assert_eq!(Item::try_from(0i64), Ok(Item::Index(0)));
+        assert_eq!(Item::try_from(3isize), Ok(Item::Index(3)));
+    }
+
+    #[test]
+    fn try_from_errors_on_negative_integers() {
+        let error = Item::try_from(-1i32).unwrap_err();
+        assert_eq!(error.value(), -1);
+        assert_eq!(error.to_string(), "`-1` is not a valid index");
+        assert!(Item::try_from(i64::MIN).is_err());
+        assert!(Item::try_from(-5isize).is_err());
+    }
+
+    #[test]
+    fn add_works_for_number() {
+        let path = KeyPath::default();
+        let result = path + 45;
+        assert_eq!(result, KeyPath::new(vec![Item::Index(45)]))
+    }
+
+    #[test]
+    fn add_works_for_str() {
+        let path = KeyPath::default();
+        let result = path + "";
+        assert_eq!(result, KeyPath::new(vec![Item::Key("".into())]))
+    }
+
+    #[test]
+    fn add_works_for_string() {
+        let path = KeyPath::default();
+        let result = path + "a".to_owned();
+        assert_eq!(result, KeyPath::new(vec![Item::Key("a".into())]))
+    }
+
+    #[test]
+    fn add_works_for_string_ref() {
+        let path = KeyPath::default();
+        let string = "abc".to_owned();
+        let string_ref = &string;
+        let result = path + string_ref;
+        assert_eq!(result, KeyPath::new(vec![Item::Key("abc".into())]))
+    }
+
+    #[test]
+    fn add_works_for_key_path() {
+        let result = path!["a", "b"] + path!["c", 1];
+        assert_eq!(result, path!["a", "b", "c", 1]);
+    }
+
+    #[test]
+    fn add_works_for_key_path_refs() {
+        let a = path!["a", "b"];
+        let b = path!["c", 1];
+        assert_eq!(&a + &b, path!["a", "b", "c", 1]);
+        assert_eq!(&a + b.clone(), path!["a", "b", "c", 1]);
+        assert_eq!(a.clone() + &b, path!["a", "b", "c", 1]);
+        assert_eq!(&a + "c", path!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn add_works_for_items_and_key_paths_together() {
+        let a = path!["a"];
+        let result = &a + 1 + path!["b", 2] + "c" + &a + Item::NextIndex;
+        assert_eq!(result[..6], path!["a", 1, "b", 2, "c", "a"][..]);
+        assert_eq!(result.last(), Some(&Item::NextIndex));
+    }
+
+    #[test]
+    fn add_works_for_empty_key_paths() {
+        let path = path!["a", 1];
+        assert_eq!(KeyPath::default() + &path, path);
+        assert_eq!(&path + KeyPath::default(), path);
+    }
+
+    #[test]
+    fn add_assign_works() {
+        let mut path = KeyPath::default();
+        path += "where";
+        path += 2;
+        path += "name".to_owned();
+        path += Item::Index(0);
+        assert_eq!(path, path!["where", 2, "name", 0]);
+    }
+
+    #[test]
+    fn add_assign_works_for_key_path() {
+        let mut path = path!["a"];
+        path += path![1, "b"];
+        path += KeyPath::default();
+        assert_eq!(path, path!["a", 1, "b"]);
+        let suffix = path!["c"];
+        path += &suffix;
+        path += &suffix;
+        assert_eq!(path, path!["a", 1, "b", "c", "c"]);
+    }
+
+    #[test]
+    fn add_assign_works_in_a_loop() {
+        let mut path = KeyPath::default();
+        for depth in 0..100 {
+            path += "child";
+            path += depth;
+        }
+        assert_eq!(path.len(), 200);
+        assert_eq!(path[198..], path!["child", 99][..]);
+    }
+
+    #[test]
+    fn from_array_works() {
+        assert_eq!(KeyPath::from(["a", "b", "c"]), path!["a", "b", "c"]);
+        assert_eq!(KeyPath::from([3, 0]), path![3, 0]);
+        assert_eq!(KeyPath::from([0usize; 0]), KeyPath::default());
+        assert_eq!(KeyPath::from(["users"]) + path![3, "email"], path!["users", 3, "email"]);
+    }
+
+    #[test]
+    fn from_vec_works() {
+        assert_eq!(KeyPath::from(vec!["a", "b"]), path!["a", "b"]);
+        assert_eq!(KeyPath::from(vec!["a".to_owned()]), path!["a"]);
+        assert_eq!(KeyPath::from(Vec::<&str>::new()), KeyPath::default());
+        assert_eq!(KeyPath::from(vec![Item::Key("a".into()), Item::Index(1)]), path!["a", 1]);
+    }
+
+    #[test]
+    fn collect_works_for_strs() {
+        let result: KeyPath = vec!["a", "b"].into_iter().collect();
+        assert_eq!(result, path!["a", "b"]);
+    }
+
+    #[test]
+    fn collect_works_for_indices() {
+        let result: KeyPath = (0..3usize).collect();
+        assert_eq!(result, path![0, 1, 2]);
+    }
+
+    #[test]
+    fn collect_works_for_items() {
+        let result: KeyPath = path!["a", 1].into_iter().chain([Item::NextIndex]).collect();
+        assert_eq!(result, path!["a", 1] + Item::NextIndex);
+        assert_eq!(core::iter::empty::<Item>().collect::<KeyPath>(), KeyPath::default());
+    }
+
+    #[test]
+    fn extend_works() {
+        let mut path = path!["root"];
+        path.extend(["x", "y"]);
+        path.extend([3, 4]);
+        path.extend(vec![Item::Key("z".into())]);
+        assert_eq!(path, path!["root", "x", "y", 3, 4, "z"]);
+    }
+
+    #[test]
+    fn extend_works_for_empty_iterator() {
+        let mut path = path!["root"];
+        path.extend(Vec::<Item>::new());
+        assert_eq!(path, path!["root"]);
+    }
+
+    #[test]
+    fn key_path_can_be_debug_printed() {
+        let path = path!["where", "items", 5, "name"];
+        let result = format!("{}", path);
+        assert_eq!(&result, "where.items.5.name");
+    }
+
+    #[test]
+    fn with_capacity_works() {
+        let mut path = KeyPath::with_capacity(8);
+        assert!(path.is_empty());
+        let capacity = path.capacity();
+        assert!(capacity >= 8);
+        for i in 0..8 {
+            path.push(i);
+        }
+        assert_eq!(path.capacity(), capacity);
+    }
+
+    #[test]
+    fn reserve_works() {
+        let mut path = path!["a"];
+        path.reserve(10);
+        assert!(path.capacity() >= 11);
+        assert_eq!(path, path!["a"]);
+    }
+
+    #[test]
+    fn into_items_works() {
+        let items = vec![Item::Key("a".into()), Item::Index(1)];
+        assert_eq!(KeyPath::new(items.clone()).into_items(), items);
+        let mut items: Vec<Item> = path!["a", 1].into();
+        items.push(Item::Key("b".into()));
+        assert_eq!(KeyPath::from(items), path!["a", 1, "b"]);
+        assert_eq!(KeyPath::default().into_items(), vec![]);
+    }
+
+    #[test]
+    fn shrink_to_fit_works() {
+        let mut path = path!["a", 1, "b", 2, "c", 3];
+        path.reserve(100);
+        path.shrink_to_fit();
+        assert!(path.capacity() >= 6 && path.capacity() < 100);
+        assert_eq!(path, path!["a", 1, "b", 2, "c", 3]);
+    }
+
+    #[test]
+    fn path_macro_allocates_once() {
+        let path = path!["a", 1, "b", 2, "c", 3, "d"];
+        assert!(path.capacity() < 2 * path.len());
+    }
+
+    #[test]
+    fn add_reserves_before_pushing() {
+        let path = path!["a", 1, "b", 2, "c", 3, "d"];
+        let result = &path + "e";
+        assert!(result.capacity() >= 8 && result.capacity() < 2 * path.len());
+        let result = &path + &path;
+        assert!(result.capacity() >= 14 && result.capacity() < 4 * path.len());
+    }
+
+    #[test]
+    fn as_slice_works() {
+        assert_eq!(path!["a", 1].as_slice(), &[Item::Key("a".into()), Item::Index(1)]);
+        assert_eq!(KeyPath::default().as_slice(), &[]);
+    }
+
+    #[test]
+    fn deref_exposes_slice_methods() {
+        let path = path!["a", "b", 1];
+        assert_eq!(path.first(), Some(&Item::Key("a".into())));
+        let windows: Vec<&[Item]> = path.windows(2).collect();
+        assert_eq!(windows, vec![&path[0..2], &path[1..3]]);
+        let reversed: Vec<&Item> = path.iter().rev().collect();
+        assert_eq!(reversed, vec![&Item::Index(1), &Item::Key("b".into()), &Item::Key("a".into())]);
+        assert_eq!(path.iter().len(), 3);
+    }
+
+    #[test]
+    fn index_still_resolves_with_deref() {
+        let path = path!["a", "b", 1];
+        let item: &Item = &path[2];
+        let range: &[Item] = &path[0..1];
+        assert_eq!(item, &Item::Index(1));
+        assert_eq!(range, &[Item::Key("a".into())]);
+    }
+
+    #[test]
+    fn index_works() {
+        let path = path!["orderBy", "name"];
+        let result = &path[0];
+        assert_eq!(result, &("orderBy".into()))
+    }
+
+    #[test]
+    fn index_with_range_works() {
+        let path = path!["orderBy", "name", 3, "good"];
+        let result = &path[0..2];
+        assert_eq!(result, &[Item::Key("orderBy".into()), Item::Key("name".into())])
+    }
+
+    #[test]
+    fn index_with_range_from_works() {
+        let path = path!["a", "b", "c"];
+        assert_eq!(&path[1..], &[Item::Key("b".into()), Item::Key("c".into())]);
+        assert_eq!(&path[3..], &[]);
+    }
+
+    #[test]
+    fn index_with_range_to_works() {
+        let path = path!["a", "b", "c"];
+        assert_eq!(&path[..2], &[Item::Key("a".into()), Item::Key("b".into())]);
+        assert_eq!(&path[..0], &[]);
+    }
+
+    #[test]
+    fn index_with_range_full_works() {
+        let path = path!["a", 1];
+        assert_eq!(&path[..], &[Item::Key("a".into()), Item::Index(1)]);
+    }
+
+    #[test]
+    fn index_with_range_inclusive_works() {
+        let path = path!["a", "b", "c"];
+        assert_eq!(&path[1..=2], &[Item::Key("b".into()), Item::Key("c".into())]);
+        assert_eq!(&path[0..=0], &[Item::Key("a".into())]);
+    }
+
+    #[test]
+    fn index_with_empty_range_works() {
+        let path = path!["a", "b", "c"];
+        assert_eq!(&path[2..2], &[]);
+    }
+
+    #[test]
+    fn get_works() {
+        let path = path!["orderBy", "name"];
+        let result = path.get(0).unwrap();
+        assert_eq!(result, &("orderBy".into()))
+    }
+
+    #[test]
+    fn item_from_static_str_borrows() {
+        let literal = "where";
+        match Item::key(literal) {
+            Item::Key(Cow::Borrowed(key)) => assert_eq!(key.as_ptr(), literal.as_ptr()),
+            item => panic!("expected a borrowed key, got {:?}", item),
+        }
+        assert!(path!["a", "b"].iter().all(|item| matches!(item, Item::Key(Cow::Borrowed(_)))));
+    }
+
+    #[test]
+    fn item_from_borrowed_str_copies() {
+        let owned = String::from("a");
+        let key: &str = &owned;
+        assert!(matches!(Item::from(key), Item::Key(Cow::Owned(_))));
+        let path = path![key, 1, "b"];
+        assert_eq!(path, path!["a", 1, "b"]);
+        assert!(path.contains(key));
+        assert_eq!(path.position(key), Some(0));
+    }
+
+    #[test]
+    fn path_macro_accepts_expressions_starting_with_literals() {
+        let path = path![1 + 1, "a".to_uppercase(), 3,];
+        assert_eq!(path, KeyPath::new(vec![Item::Index(2), Item::from("A"), Item::Index(3)]));
+        assert_eq!(path![], KeyPath::default());
+    }
+
+    #[test]
+    fn item_from_string_owns() {
+        assert!(matches!(Item::from(String::from("a")), Item::Key(Cow::Owned(_))));
+        assert!(matches!(Item::from(&String::from("a")), Item::Key(Cow::Owned(_))));
+        assert_eq!(Item::from(String::from("a")), Item::from("a"));
+    }
+
+    #[test]
+    fn as_key_mut_makes_borrowed_keys_owned() {
+        let mut item = Item::from("a");
+        item.as_key_mut().unwrap().push('b');
+        assert!(matches!(&item, Item::Key(Cow::Owned(key)) if key == "ab"));
+    }
+
+    #[test]
+    fn get_mut_works() {
+        let mut path = path!["user", "name"];
+        if let Some(key) = path.get_mut(1).and_then(Item::as_key_mut) {
+            key.push_str("_v2");
+        }
+        assert_eq!(path, path!["user", "name_v2"]);
+        assert_eq!(path.get_mut(2), None);
+    }
+
+    #[test]
+    fn iter_mut_works() {
+        let mut path = path!["items", 0, "tags", 4];
+        for index in path.iter_mut().filter_map(Item::as_index_mut) {
+            *index += 1;
+        }
+        assert_eq!(path, path!["items", 1, "tags", 5]);
+    }
+
+    #[test]
+    fn as_key_mut_and_as_index_mut_work() {
+        assert_eq!(Item::Key("a".into()).as_index_mut(), None);
+        assert_eq!(Item::Index(1).as_key_mut(), None);
+        assert_eq!(Item::NextIndex.as_key_mut(), None);
+        assert_eq!(Item::NextIndex.as_index_mut(), None);
+    }
+
+    #[test]
+    fn last_works() {
+        let path = path!["orderBy", "name"];
+        let result = path.last().unwrap();
+        assert_eq!(result, &("name".into()))
+    }
+
+    #[test]
+    fn empty_works_in_const_context() {
+        const ROOT: KeyPath = KeyPath::empty();
+        assert_eq!(ROOT, KeyPath::default());
+        assert!(ROOT.is_empty());
+        assert_eq!(&ROOT + "a", path!["a"]);
+    }
+
+    #[test]
+    fn first_works() {
+        assert_eq!(path!["orderBy", "name"].first(), Some(&Item::Key("orderBy".into())));
+        assert_eq!(KeyPath::default().first(), None);
+    }
+
+    #[test]
+    fn deref_gives_slice_methods() {
+        let path = path!["a", "b", 1];
+        assert_eq!(path.binary_search(&Item::Key("b".into())), Ok(1));
+        assert_eq!(path.iter().rposition(Item::is_key), Some(1));
+        assert_eq!(path.to_vec(), path.clone().into_items());
+        assert!(path.is_sorted());
+    }
+
+    #[test]
+    fn deref_mut_gives_mutable_slice_methods() {
+        let mut path = path![2, "b", "a", 0];
+        path.sort();
+        assert_eq!(path, path!["a", "b", 0, 2]);
+        path.rotate_left(2);
+        assert_eq!(path, path![0, 2, "a", "b"]);
+        path.split_at_mut(2).1.reverse();
+        assert_eq!(path.to_string(), "0.2.b.a");
+    }
+
+    #[test]
+    fn borrow_works_for_map_lookups() {
+        use std::collections::{BTreeMap, HashMap};
+        let items = [Item::Key("a".into()), Item::Index(1)];
+        let mut tree = BTreeMap::new();
+        tree.insert(path!["a", 1], "tree");
+        tree.insert(path!["a"], "parent");
+        assert_eq!(tree.get(&items[..]), Some(&"tree"));
+        assert_eq!(tree.get(&items[..1]), Some(&"parent"));
+        assert_eq!(tree.get(&[Item::Key("a".into()), Item::Key("1".into())][..]), None);
+        let mut hash = HashMap::new();
+        hash.insert(path!["a", 1], "hash");
+        assert_eq!(hash.get(&items[..]), Some(&"hash"));
+    }
+
+    #[test]
+    #[allow(clippy::useless_asref)]
+    fn as_ref_works() {
+        let path = path!["a", "b"];
+        let path2: &KeyPath = path.as_ref();
+        let path3: &KeyPath = path2.as_ref();
+        assert_eq!(&path, path2);
+        assert_eq!(path2, path3);
+        let items: &[Item] = path.as_ref();
+        assert_eq!(items, path.as_slice());
+
+    }
+
+    #[test]
+    fn to_string_works() {
+        let path = path!["a", 2, "3"];
+        assert_eq!(&path.to_string(), "a.2.3");
+    }
+
+    #[test]
+    fn ref_to_string_works() {
+        let path = path!["a", 2, "3"];
+        let path_ref = &path;
+        assert_eq!(&path_ref.to_string(), "a.2.3");
+    }
+
+    #[test]
+    fn to_string_escapes_dots_in_keys() {
+        let path = path!["a.b", "c"];
+        assert_eq!(&path.to_string(), r"a\.b.c");
+        assert_ne!(path.to_string(), path!["a", "b", "c"].to_string());
+    }
+
+    #[test]
+    fn to_string_escapes_backslashes_in_keys() {
+        let path = path![r"a\", 1];
+        assert_eq!(&path.to_string(), r"a\\.1");
+    }
+
+    #[test]
+    fn to_string_escapes_consecutive_and_edge_characters() {
+        let path = path![r".a..b\\", "-", 7];
+        assert_eq!(&path.to_string(), r"\.a\.\.b\\\\.\-.7");
+    }
+
+    #[test]
+    fn display_honors_width_and_alignment() {
+        let path = path!["a.b", 1];
+        assert_eq!(format!("{:>8}", path), r"  a\.b.1");
+        assert_eq!(format!("{:<8}|", path), r"a\.b.1  |");
+        assert_eq!(format!("{:*^9}", path), r"*a\.b.1**");
+        assert_eq!(format!("{:>width$}", path, width = 3), r"a\.b.1");
+    }
+
+    #[test]
+    fn display_honors_precision() {
+        let path = path!["where", "items", 5];
+        assert_eq!(format!("{:.5}", path), "where");
+        assert_eq!(format!("{:>8.5}", path), "   where");
+        assert_eq!(format!("{:.50}", path), "where.items.5");
+    }
+
+    #[test]
+    fn alternate_display_uses_brackets() {
+        let path = path!["where", "items", 5, "name"];
+        assert_eq!(format!("{:#}", path), "where.items[5].name");
+        assert_eq!(format!("{}", path), "where.items.5.name");
+        assert_eq!(format!("{:#}", path![0, 1, "a"]), "[0][1].a");
+        assert_eq!(format!("{:>#12}|", path!["a", 1]), "        a[1]|");
+    }
+
+    #[test]
+    fn alternate_display_works_for_empty() {
+        assert_eq!(format!("{:#}", KeyPath::default()), "");
+    }
+
+    #[test]
+    fn item_alternate_display_uses_brackets() {
+        assert_eq!(format!("{:#}", Item::Index(5)), "[5]");
+        assert_eq!(format!("{:#}", Item::NextIndex), "[-]");
+        assert_eq!(format!("{:#}", Item::Key("a".into())), "a");
+        assert_eq!(format!("{}", Item::Index(5)), "5");
+    }
+
+    #[test]
+    fn item_display_honors_formatter_flags() {
+        assert_eq!(format!("{:>4}", Item::Key("ab".into())), "  ab");
+        assert_eq!(format!("{:<4}|", Item::Index(12)), "12  |");
+        assert_eq!(format!("{:.1}", Item::Index(12)), "1");
+        assert_eq!(format!("{:^3}", Item::NextIndex), " - ");
+        assert_eq!(format!("{}", Item::Index(12)), "12");
+    }
+
+    #[test]
+    fn to_string_keeps_empty_keys_empty() {
+        let path = path!["a", "", "b"];
+        assert_eq!(&path.to_string(), "a..b");
+    }
+
+    #[test]
+    fn eq_str_works() {
+        let path = path!["a", "b", 1];
+        assert!(path == "a.b.1");
+        assert!("a.b.1" == path);
+        assert!(path == *"a.b.1");
+        assert!(*"a.b.1" == path);
+        assert!(path != "a.b");
+        assert!(path != "a.b.1.");
+        assert!(path != "a.b.2");
+        assert_eq!(KeyPath::default(), "");
+    }
+
+    #[test]
+    fn eq_str_honors_escaping() {
+        assert!(path!["a.b", 1] == r"a\.b.1");
+        assert!(path!["a.b", 1] != "a.b.1");
+        assert!(path!["a", "b", 1] != r"a\.b.1");
+    }
+
+    #[test]
+    fn rendered_len_works() {
+        let paths = [
+            KeyPath::default(),
+            path![""],
+            path!["a", "", "b"],
+            path!["ünïcödé", "日本語", 0],
+            path![r"a.b\c", 9, 10, 99, 100, usize::MAX],
+            KeyPath::new(vec![Item::Key("a".into()), Item::NextIndex]),
+        ];
+        for path in paths {
+            assert_eq!(path.rendered_len(), path.to_string().len(), "{:?}", path);
+        }
+    }
+
+    #[test]
+    fn write_to_appends() {
+        let mut out = "at ".to_owned();
+        path!["a.b", 1].write_to(&mut out);
+        out.push(';');
+        KeyPath::default().write_to(&mut out);
+        assert_eq!(out, r"at a\.b.1;");
+        assert_eq!(String::from(path!["x", 2]), "x.2");
+    }
+
+    #[test]
+    fn to_string_unescaped_works() {
+        let path = path!["a.b", r"c\", 1];
+        assert_eq!(&path.to_string_unescaped(), r"a.b.c\.1");
+    }
+
+    #[test]
+    fn next_index_accessors_work() {
+        let item = Item::NextIndex;
+        assert!(item.is_next_index());
+        assert!(!item.is_key());
+        assert!(!item.is_index());
+        assert_eq!(item.as_key(), None);
+        assert_eq!(item.as_index(), None);
+        assert!(!Item::Index(0).is_next_index());
+    }
+
+    #[test]
+    fn next_index_displays_as_dash() {
+        let path = path!["items"] + Item::NextIndex;
+        assert_eq!(&path.to_string(), "items.-");
+    }
+
+    #[test]
+    fn item_ord_works() {
+        assert!(Item::Index(2) < Item::Index(10));
+        assert!(Item::Key("10".into()) < Item::Key("2".into()));
+        assert!(Item::Key("z".into()) < Item::Index(0));
+        assert!(Item::Index(usize::MAX) < Item::NextIndex);
+    }
+
+    #[test]
+    fn key_path_ord_works() {
+        let mut paths = vec![path!["a", 10], path!["a", "b"], path!["a", 2], path!["a"], path![0]];
+        paths.sort();
+        assert_eq!(paths, vec![path!["a"], path!["a", "b"], path!["a", 2], path!["a", 10], path![0]]);
+    }
+
+    #[test]
+    fn depth_and_counts_work() {
+        let path = path!["a", 2, "b", 3, 4];
+        assert_eq!(path.depth(), 5);
+        assert_eq!(path.key_count(), 2);
+        assert_eq!(path.index_count(), 3);
+        let path = KeyPath::new(vec![Item::Key("a".into()), Item::NextIndex]);
+        assert_eq!((path.depth(), path.key_count(), path.index_count()), (2, 1, 0));
+    }
+
+    #[test]
+    fn depth_and_counts_work_for_empty() {
+        let path = KeyPath::default();
+        assert_eq!((path.depth(), path.key_count(), path.index_count()), (0, 0, 0));
+    }
+
+    #[test]
+    fn retain_works() {
+        let mut path = path!["a", 2, "b", 3];
+        path.retain(Item::is_key);
+        assert_eq!(path, path!["a", "b"]);
+        path.retain(|_| true);
+        assert_eq!(path, path!["a", "b"]);
+        path.retain(|_| false);
+        assert!(path.is_empty());
+    }
+
+    #[test]
+    fn filtered_works() {
+        let path = path!["a", 2, "b", 3];
+        assert_eq!(path.filtered(Item::is_key), path!["a", "b"]);
+        assert_eq!(path.filtered(|_| true), path);
+        assert_eq!(path.filtered(|i| i.as_index() > Some(2)), path![3]);
+    }
+
+    #[test]
+    fn dedup_works() {
+        let mut path = path!["a", "a", "b", 1, 1, "1"];
+        path.dedup();
+        assert_eq!(path, path!["a", "b", 1, "1"]);
+        let mut path = KeyPath::default();
+        path.dedup();
+        assert!(path.is_empty());
+    }
+
+    #[test]
+    fn deduped_keeps_non_adjacent_duplicates() {
+        let path = path!["a", "a", "b", "b", "a"];
+        assert_eq!(path.deduped(), path!["a", "b", "a"]);
+        assert_eq!(path!["a", "b", "a"].deduped(), path!["a", "b", "a"]);
+        assert_eq!(KeyPath::default().deduped(), KeyPath::default());
+    }
+
+    #[test]
+    fn map_works() {
+        let path = path!["Users", 3, "EMail"];
+        let lowercased = path.map(|i| match i {
+            Item::Key(s) => Item::Key(s.to_lowercase().into()),
+            other => other.clone(),
+        });
+        assert_eq!(lowercased, path!["users", 3, "email"]);
+    }
+
+    #[test]
+    fn map_items_works() {
+        let path = path!["a", "items", 3];
+        let mapped = path.map_items(|i| match i {
+            Item::Key(key) => Item::Key(key + "_v2"),
+            other => other,
+        });
+        assert_eq!(mapped, path!["a_v2", "items_v2", 3]);
+    }
+
+    #[test]
+    fn retain_derives_type_path() {
+        let mut path: KeyPath = "a.items.3.name".parse().unwrap();
+        path.retain(Item::is_key);
+        assert_eq!(path.to_string(), "a.items.name");
+    }
+
+    #[test]
+    fn map_works_for_indices() {
+        let path = path!["a", 3, "b", 7];
+        let zeroed = path.map(|i| if i.is_index() { Item::Index(0) } else { i.clone() });
+        assert_eq!(zeroed, path!["a", 0, "b", 0]);
+        assert_eq!(path, path!["a", 3, "b", 7]);
+    }
+
+    #[test]
+    fn push_and_pop_works() {
+        let mut path = path!["a", 1];
+        path.push("b");
+        assert_eq!(path, path!["a", 1, "b"]);
+        assert_eq!(path.pop(), Some(Item::Key("b".into())));
+        assert_eq!(path, path!["a", 1]);
+        assert_eq!(KeyPath::default().pop(), None);
+    }
+
+    #[test]
+    fn push_and_pop_update_len_last_and_iter() {
+        let mut path = KeyPath::default();
+        path.push("a");
+        path.push(2);
+        assert_eq!(path.len(), 2);
+        assert_eq!(path.last(), Some(&Item::Index(2)));
+        assert_eq!(path.iter().cloned().collect::<Vec<_>>(), vec![Item::Key("a".into()), Item::Index(2)]);
+        path.pop();
+        assert_eq!(path.len(), 1);
+        assert_eq!(path.last(), Some(&Item::Key("a".into())));
+        assert_eq!(path.iter().count(), 1);
+    }
+
+    #[test]
+    fn push_and_pop_reuse_one_path_for_a_traversal() {
+        fn walk(depth: usize, path: &mut KeyPath, leaves: &mut Vec<String>) {
+            if depth == 0 {
+                leaves.push(path.to_string());
+                return;
+            }
+            for i in 0..2 {
+                path.push(i);
+                walk(depth - 1, path, leaves);
+                path.pop();
+            }
+        }
+        let mut path = KeyPath::with_capacity(3);
+        let capacity = path.capacity();
+        let mut leaves = vec![];
+        walk(3, &mut path, &mut leaves);
+        assert_eq!(leaves.len(), 8);
+        assert_eq!(leaves[5], "1.0.1");
+        assert!(path.is_empty());
+        assert_eq!(path.capacity(), capacity);
+    }
+
+    #[test]
+    fn push_front_works() {
+        let mut path = path!["name"];
+        path.push_front("user");
+        path.push_front(0);
+        assert_eq!(path, path![0, "user", "name"]);
+    }
+
+    #[test]
+    fn prepend_works() {
+        let mut path = path!["name"];
+        path.prepend(path!["users", 3]);
+        assert_eq!(path, path!["users", 3, "name"]);
+        path.prepend(KeyPath::default());
+        assert_eq!(path, path!["users", 3, "name"]);
+        path.prepend([Item::Key("db".into())]);
+        assert_eq!(path.to_string(), "db.users.3.name");
+    }
+
+    #[test]
+    fn prepend_builds_a_deep_path() {
+        let mut path = KeyPath::default();
+        for i in (0..100).rev() {
+            path.prepend(path![i]);
+        }
+        let expected: Vec<String> = (0..100).map(|i: usize| i.to_string()).collect();
+        assert_eq!(path.len(), 100);
+        assert_eq!(path.to_string(), expected.join("."));
+    }
+
+    #[test]
+    fn insert_works() {
+        let mut path = path!["b"];
+        path.insert(0, "a");
+        path.insert(1, 5);
+        path.insert(3, "c");
+        assert_eq!(path, path!["a", 5, "b", "c"]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_panics_past_the_end() {
+        path!["a"].insert(2, "b");
+    }
+
+    #[test]
+    fn remove_works() {
+        let mut path = path!["a", "b", "c", "d"];
+        assert_eq!(path.remove(3), Item::Key("d".into()));
+        assert_eq!(path.remove(1), Item::Key("b".into()));
+        assert_eq!(path.remove(0), Item::Key("a".into()));
+        assert_eq!(path, path!["c"]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn remove_panics_out_of_bounds() {
+        KeyPath::default().remove(0);
+    }
+
+    #[test]
+    fn drain_works() {
+        let mut path = path!["a", 1, "b", 2, "c"];
+        let drained: Vec<Item> = path.drain(1..3).collect();
+        assert_eq!(drained, vec![Item::Index(1), Item::Key("b".into())]);
+        assert_eq!(path, path!["a", 2, "c"]);
+        assert_eq!(path.drain(..).len(), 3);
+        assert!(path.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn drain_panics_past_the_end() {
+        path!["a"].drain(..2).for_each(drop);
+    }
+
+    #[test]
+    fn edits_show_in_iter_and_display() {
+        let mut path = path!["users", 3, "email", "primary"];
+        path.truncate(3);
+        path.insert(1, "admins");
+        assert_eq!(path.remove(2), Item::Index(3));
+        assert_eq!(path.to_string(), "users.admins.email");
+        assert_eq!(path.iter().len(), 3);
+        assert_eq!(path.drain(1..2).count(), 1);
+        assert_eq!(path.iter().next_back(), Some(&Item::Key("email".into())));
+        assert_eq!(path.to_string(), "users.email");
+    }
+
+    #[test]
+    fn swap_works() {
+        let mut path = path!["a", 1, "c"];
+        path.swap(0, 2);
+        assert_eq!(path, path!["c", 1, "a"]);
+        path.swap(1, 1);
+        assert_eq!(path, path!["c", 1, "a"]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn swap_panics_out_of_bounds() {
+        path!["a"].swap(0, 1);
+    }
+
+    #[test]
+    fn swap_remove_works() {
+        let mut path = path!["a", "b", "c", "d"];
+        assert_eq!(path.swap_remove(1), Item::Key("b".into()));
+        assert_eq!(path, path!["a", "d", "c"]);
+        assert_eq!(path.swap_remove(2), Item::Key("c".into()));
+        assert_eq!(path, path!["a", "d"]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn swap_remove_panics_out_of_bounds() {
+        KeyPath::default().swap_remove(0);
+    }
+
+    #[test]
+    fn try_remove_works() {
+        let mut path = path!["a", 1];
+        assert_eq!(path.try_remove(2), None);
+        assert_eq!(path.try_remove(1), Some(Item::Index(1)));
+        assert_eq!(path.try_remove(0), Some(Item::Key("a".into())));
+        assert_eq!(path.try_remove(0), None);
+    }
+
+    #[test]
+    fn append_works() {
+        let mut path = path!["a"];
+        path.append(path![1, "b"]);
+        assert_eq!(path, path!["a", 1, "b"]);
+        path.append(KeyPath::from(&path[1..]));
+        assert_eq!(path, path!["a", 1, "b", 1, "b"]);
+    }
+
+    #[test]
+    fn append_works_for_empty_path() {
+        let mut path = path!["a", 1];
+        path.append(KeyPath::default());
+        assert_eq!(path, path!["a", 1]);
+    }
+
+    #[test]
+    fn contains_key_works() {
+        let path = path!["users", 3, "password"];
+        assert!(path.contains_key("password"));
+        assert!(!path.contains_key("3"));
+        assert!(!path!["3"].contains_index(3));
+        assert!(path!["3"].contains_key("3"));
+    }
+
+    #[test]
+    fn contains_index_works() {
+        let path = path!["users", 3, "password"];
+        assert!(path.contains_index(3));
+        assert!(!path.contains_index(0));
+        assert!(!KeyPath::default().contains_index(0));
+    }
+
+    #[test]
+    fn contains_works() {
+        let path = path!["a", "b", "a"];
+        assert!(path.contains("b"));
+        assert!(!path.contains(5));
+        assert!(!KeyPath::default().contains("a"));
+    }
+
+    #[test]
+    fn position_works() {
+        let path = path!["a", "b", "a", 3];
+        assert_eq!(path.position("a"), Some(0));
+        assert_eq!(path.position("b"), Some(1));
+        assert_eq!(path.position(3), Some(3));
+        assert_eq!(path.position("c"), None);
+    }
+
+    #[test]
+    fn contains_and_position_tell_keys_from_indices() {
+        let path = path!["items", "2"];
+        assert!(!path.contains(2));
+        assert_eq!(path.position(2), None);
+        assert_eq!(path.position("2"), Some(1));
+    }
+
+    #[test]
+    fn reverse_works() {
+        let mut path = path!["a", "b", 1];
+        path.reverse();
+        assert_eq!(path, path![1, "b", "a"]);
+        assert_eq!(path!["a", "b", 1].reversed(), path![1, "b", "a"]);
+    }
+
+    #[test]
+    fn reverse_works_for_empty_and_single_item() {
+        let mut empty = KeyPath::default();
+        empty.reverse();
+        assert_eq!(empty, KeyPath::default());
+        assert_eq!(KeyPath::default().reversed(), KeyPath::default());
+        assert_eq!(path!["a"].reversed(), path!["a"]);
+    }
+
+    #[test]
+    fn reversed_twice_is_identity() {
+        let path = path!["where", "items", 5, "name"];
+        assert_eq!(path.reversed().reversed(), path);
+    }
+
+    #[test]
+    fn truncate_works() {
+        let mut path = path!["a", "b", "c"];
+        path.truncate(5);
+        assert_eq!(path, path!["a", "b", "c"]);
+        path.truncate(1);
+        assert_eq!(path, path!["a"]);
+        path.truncate(0);
+        assert_eq!(path, KeyPath::default());
+    }
+
+    #[test]
+    fn take_works() {
+        let path = path!["a", "b", "c"];
+        assert_eq!(path.take(2), path!["a", "b"]);
+        assert_eq!(path.take(0), KeyPath::default());
+        assert_eq!(path.take(10), path);
+    }
+
+    #[test]
+    fn split_at_works() {
+        let path = path!["a", "b", "c", "d"];
+        assert_eq!(path.split_at(2), (path!["a", "b"], path!["c", "d"]));
+        assert_eq!(path.split_at(0), (KeyPath::default(), path.clone()));
+        assert_eq!(path.split_at(4), (path.clone(), KeyPath::default()));
+        assert_eq!(path.clone().into_split_at(1), (path!["a"], path!["b", "c", "d"]));
+        assert_eq!(path.clone().into_split_at(4), (path, KeyPath::default()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_at_panics_past_the_end() {
+        path!["a"].split_at(2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn into_split_at_panics_past_the_end() {
+        path!["a"].into_split_at(2);
+    }
+
+    #[test]
+    fn prefix_and_suffix_clamp() {
+        let path = path!["a", "b", "c", "d"];
+        assert_eq!(path.prefix(2), path!["a", "b"]);
+        assert_eq!(path.suffix(2), path!["c", "d"]);
+        assert_eq!(path.prefix(9), path);
+        assert_eq!(path.suffix(9), path);
+        assert_eq!(path.suffix(0), KeyPath::default());
+    }
+
+    #[test]
+    fn split_first_works() {
+        let path = path!["a", "b", 1];
+        let (head, tail) = path.split_first().unwrap();
+        assert_eq!(head, &Item::Key("a".into()));
+        assert_eq!(tail, &[Item::Key("b".into()), Item::Index(1)]);
+        assert_eq!(KeyPath::default().split_first(), None);
+    }
+
+    #[test]
+    fn split_last_works() {
+        let path = path!["a", "b", 1];
+        let (last, rest) = path.split_last().unwrap();
+        assert_eq!(last, &Item::Index(1));
+        assert_eq!(rest, &[Item::Key("a".into()), Item::Key("b".into())]);
+        assert_eq!(KeyPath::default().split_last(), None);
+    }
+
+    #[test]
+    fn split_first_walks_without_allocating() {
+        fn keys(items: &[Item]) -> usize {
+            match items.split_first() {
+                Some((head, tail)) => head.is_key() as usize + keys(tail),
+                None => 0,
+            }
+        }
+        assert_eq!(keys(&path!["a", 0, "b", "c"]), 3);
+    }
+
+    #[test]
+    fn into_split_first_works() {
+        assert_eq!(path!["a", "b", 1].into_split_first(), Some((Item::Key("a".into()), path!["b", 1])));
+        assert_eq!(path!["a"].into_split_first(), Some((Item::Key("a".into()), KeyPath::default())));
+        assert_eq!(KeyPath::default().into_split_first(), None);
+    }
+
+    #[test]
+    fn into_split_last_works() {
+        assert_eq!(path!["a", "b", 1].into_split_last(), Some((Item::Index(1), path!["a", "b"])));
+        assert_eq!(KeyPath::default().into_split_last(), None);
+    }
+
+    #[test]
+    fn parent_works() {
+        assert_eq!(path!["a", "b", 1].parent(), Some(path!["a", "b"]));
+        assert_eq!(path!["a"].parent(), Some(KeyPath::default()));
+        assert_eq!(KeyPath::default().parent(), None);
+    }
+
+    #[test]
+    fn ancestors_works() {
+        let path = path!["a", "b", 3, "c"];
+        let ancestors: Vec<KeyPath> = path.ancestors().map(KeyPath::from).collect();
+        assert_eq!(ancestors, vec![path!["a", "b", 3], path!["a", "b"], path!["a"], KeyPath::default()]);
+        assert_eq!(path.ancestors().len(), 4);
+        assert_eq!(KeyPath::default().ancestors().next(), None);
+    }
+
+    #[test]
+    fn ancestors_inclusive_works() {
+        let path = path!["a", 1];
+        let ancestors: Vec<&[Item]> = path.ancestors_inclusive().collect();
+        assert_eq!(ancestors, vec![path.as_slice(), &path[..1], &[]]);
+        assert_eq!(KeyPath::default().ancestors_inclusive().collect::<Vec<_>>(), vec![&[] as &[Item]]);
+    }
+
+    #[test]
+    fn starts_with_works() {
+        let path = path!["a", "b", 1];
+        assert!(path.starts_with(&path!["a", "b"]));
+        assert!(path.starts_with(KeyPath::default()));
+        assert!(path.starts_with(&path));
+        assert!(!path!["a", "b"].starts_with(&path));
+        assert!(!path.starts_with(&path!["b"]));
+    }
+
+    #[test]
+    fn starts_with_accepts_paths_and_slices() {
+        let path = path!["user", "settings", "theme"];
+        assert!(path.starts_with(path!["user", "settings"]));
+        assert!(path.starts_with([Item::Key("user".into())]));
+        assert!(path.starts_with(&path[..2]));
+        assert!(path.starts_with(vec![]));
+        assert!(!path.starts_with(path!["user", "settings", "theme", "x"]));
+    }
+
+    #[test]
+    fn ends_with_works() {
+        let path = path!["a", "b", 1];
+        assert!(path.ends_with(&path!["b", 1]));
+        assert!(path.ends_with(KeyPath::default()));
+        assert!(path.ends_with(&path));
+        assert!(!path![1].ends_with(&path));
+        assert!(!path.ends_with(&path!["1"]));
+    }
+
+    #[test]
+    fn ends_with_accepts_paths_and_slices() {
+        let path = path!["a", "b", 1];
+        assert!(path.ends_with(path!["b", 1]));
+        assert!(path.ends_with([Item::Index(1)]));
+        assert!(path.ends_with(&path[1..]));
+        assert!(!path.ends_with(path!["x", "a", "b", 1]));
+    }
+
+    #[test]
+    fn strip_prefix_works() {
+        let path = path!["user", "address", "city"];
+        assert_eq!(path.strip_prefix(&path!["user", "address"]), Some(path!["city"]));
+        assert_eq!(path.strip_prefix(&path!["address"]), None);
+        assert_eq!(path!["user"].strip_prefix(&path), None);
+    }
+
+    #[test]
+    fn strip_prefix_works_for_empty_and_full_prefix() {
+        let path = path!["user", "address", "city"];
+        assert_eq!(path.strip_prefix(KeyPath::default()), Some(path.clone()));
+        assert_eq!(path.strip_prefix(&path), Some(KeyPath::default()));
+    }
+
+    #[test]
+    fn strip_prefix_distinguishes_keys_from_indices() {
+        let path = path!["a", 1, "b"];
+        assert_eq!(path.strip_prefix(path!["a", "1"]), None);
+        assert_eq!(path.strip_prefix([Item::Key("a".into()), Item::Index(1)]), Some(path!["b"]));
+    }
+
+    #[test]
+    fn is_ancestor_of_works() {
+        assert!(path!["a"].is_ancestor_of(&path!["a", "b"]));
+        assert!(KeyPath::default().is_ancestor_of(&path!["a"]));
+        assert!(!path!["a", "b"].is_ancestor_of(&path!["a"]));
+        assert!(!path!["a"].is_ancestor_of(&path!["b", "a"]));
+        assert!(!path!["a", 1].is_ancestor_of(&path!["a", "1", "b"]));
+    }
+
+    #[test]
+    fn is_descendant_of_works() {
+        assert!(path!["a", "b"].is_descendant_of(&path!["a"]));
+        assert!(!path!["a"].is_descendant_of(&path!["a", "b"]));
+        assert!(!path!["b"].is_descendant_of(&path!["a"]));
+    }
+
+    #[test]
+    fn equal_paths_are_neither_ancestor_nor_descendant() {
+        let path = path!["a", 1];
+        assert!(!path.is_ancestor_of(&path));
+        assert!(!path.is_descendant_of(&path));
+        assert!(!path.is_ancestor_of(&path!["a", 1]));
+        assert!(!KeyPath::default().is_ancestor_of(&KeyPath::default()));
+    }
+
+    #[test]
+    fn strip_suffix_works() {
+        let path = path!["user", "settings", "theme", "color"];
+        assert_eq!(path.strip_suffix(path!["theme", "color"]), Some(path!["user", "settings"]));
+        assert_eq!(path.strip_suffix(&path), Some(KeyPath::default()));
+        assert_eq!(path.strip_suffix(KeyPath::default()), Some(path.clone()));
+        assert_eq!(path.strip_suffix(path!["theme"]), None);
+        assert_eq!(path!["a", 1].strip_suffix(path!["1"]), None);
+    }
+
+    #[test]
+    fn strip_slice_variants_borrow() {
+        let path = path!["user", "settings", "theme"];
+        assert_eq!(path.strip_prefix_slice(path!["user"]), Some(&path[1..]));
+        assert_eq!(path.strip_suffix_slice(path!["theme"]), Some(&path[..2]));
+        assert_eq!(path.strip_prefix_slice(path!["theme"]), None);
+    }
+
+    #[test]
+    fn common_prefix_works() {
+        let path = path!["a", "b", "c"];
+        assert_eq!(path.common_prefix(&path!["a", "b", "d"]), path!["a", "b"]);
+        assert_eq!(path.common_prefix(&path!["x", "b", "c"]), KeyPath::default());
+    }
+
+    #[test]
+    fn zip_stops_at_the_shorter_path() {
+        let (a, b) = (path!["a", 1, "b"], path!["a", "1"]);
+        let pairs: Vec<_> = a.zip(&b).collect();
+        assert_eq!(pairs, vec![(&a[0], &b[0]), (&a[1], &b[1])]);
+        assert_eq!(a.zip(&b).position(|(x, y)| x != y), Some(1));
+        assert_eq!(a.zip(&KeyPath::default()).len(), 0);
+    }
+
+    #[test]
+    fn zip_works_for_equal_lengths() {
+        let (a, b) = (path!["a", 1, "b"], path!["x", 1, "y"]);
+        assert_eq!(a.zip(&b).len(), 3);
+        assert_eq!(a.zip(&b).filter(|(x, y)| x == y).count(), 1);
+    }
+
+    #[test]
+    fn common_prefix_len_works() {
+        let path = path!["a", 3, "c"];
+        assert_eq!(path.common_prefix_len(&path!["a", 3, "d"]), 2);
+        assert_eq!(path.common_prefix_len(&path!["a", "3", "c"]), 1);
+        assert_eq!(path.common_prefix_len(&path), 3);
+        assert_eq!(path.common_prefix_len(&KeyPath::default()), 0);
+    }
+
+    #[test]
+    fn common_prefix_of_works() {
+        let paths = [path!["users", 3, "email"], path!["users", 3, "name"], path!["users", 3]];
+        assert_eq!(KeyPath::common_prefix_of(&paths), path!["users", 3]);
+        assert_eq!(KeyPath::common_prefix_of([&path!["a", 1], &path!["a", "1"]]), path!["a"]);
+        assert_eq!(KeyPath::common_prefix_of(&paths[..1]), paths[0]);
+        assert_eq!(KeyPath::common_prefix_of(&[]), KeyPath::default());
+    }
+
+    #[test]
+    fn common_prefix_works_for_identical_and_nested_paths() {
+        let path = path!["a", "b", "c"];
+        assert_eq!(path.common_prefix(&path), path);
+        assert_eq!(path.common_prefix(&path!["a", "b"]), path!["a", "b"]);
+        assert_eq!(path!["a"].common_prefix(&path), path!["a"]);
+    }
+
+    #[test]
+    fn common_prefix_distinguishes_key_and_index() {
+        assert_eq!(path!["a", "2", "b"].common_prefix(&path!["a", 2, "b"]), path!["a"]);
+    }
+
+    #[test]
+    fn hash_works() {
+        use std::collections::HashSet;
+        let mut set = HashSet::new();
+        set.insert(path!["a", 1]);
+        set.insert(path!["a", 1]);
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn hash_distinguishes_key_and_index() {
+        use std::collections::HashSet;
+        let mut set = HashSet::new();
+        set.insert(path!["1"]);
+        set.insert(path![1]);
+        assert_ne!(path!["1"], path![1]);
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn join_with_works() {
+        let path = path!["database", "pool", 0, "url"];
+        assert_eq!(&path.join_with("/"), "database/pool/0/url");
+        assert_eq!(&path.join_with("__"), "database__pool__0__url");
+        assert_eq!(&KeyPath::default().join_with("::"), "");
+    }
+
+    #[test]
+    fn to_string_with_separator_works() {
+        let path = path!["a", 2, "b"];
+        assert_eq!(path.to_string_with_separator("/"), "a/2/b");
+        assert_eq!(path.to_string_with_separator(" -> "), "a -> 2 -> b");
+        assert_eq!(KeyPath::default().to_string_with_separator(" -> "), "");
+        assert_eq!(path.to_string(), "a.2.b");
+    }
+
+    #[test]
+    fn display_with_works() {
+        let path = path!["a.b", 2];
+        assert_eq!(format!("{}", path.display_with("/")), "a.b/2");
+        assert_eq!(format!("<{}>", KeyPath::default().display_with("/")), "<>");
+    }
+
+    #[test]
+    fn iter_works() {
+        let path = path!["a", 2, "3"];
+        let path = &path;
+        let mut result = "".to_owned();
+        for item in path {
+            result += format!("{}", item).as_str();
+        }
+        assert_eq!(&result, "a23");
+    }
+
+    #[test]
+    fn windows_works() {
+        let path = path!["a", "b", "c"];
+        let windows: Vec<&[Item]> = path.windows(2).collect();
+        assert_eq!(windows, vec![&path[0..2], &path[1..3]]);
+        assert_eq!(path.windows(4).count(), 0);
+        assert!(!path.windows(2).any(|w| w[0] == w[1]));
+        assert!(path!["a", "a"].windows(2).any(|w| w[0] == w[1]));
+    }
+
+    #[test]
+    fn chunks_works() {
+        let path = path!["a", 1, "b", 2, "c"];
+        let chunks: Vec<&[Item]> = path.chunks(2).collect();
+        assert_eq!(chunks, vec![&path[0..2], &path[2..4], &path[4..]]);
+        assert_eq!(path.chunks(9).count(), 1);
+        assert_eq!(KeyPath::default().chunks(2).count(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn windows_panics_for_zero_size() {
+        path!["a"].windows(0).for_each(drop);
+    }
+
+    #[test]
+    #[should_panic]
+    fn chunks_panics_for_zero_size() {
+        path!["a"].chunks(0).for_each(drop);
+    }
+
+    #[test]
+    fn keys_and_indices_work() {
+        let path = path!["a", 2, "b", 3];
+        assert_eq!(path.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(path.indices().collect::<Vec<_>>(), vec![2, 3]);
+    }
+
+    #[test]
+    fn keys_compare_paths_regardless_of_indices() {
+        let path = path!["user", 0, "email"];
+        assert!(path.keys().eq(["user", "email"]));
+        assert!(path.keys().eq(path!["user", 5, "email"].keys()));
+        assert_eq!(path.keys().next_back(), Some("email"));
+        assert_eq!(path!["a", 1, 2].indexes().rev().collect::<Vec<_>>(), vec![2, 1]);
+    }
+
+    #[test]
+    fn keys_and_indices_work_for_uniform_paths() {
+        assert_eq!(path![0, 1].keys().count(), 0);
+        assert_eq!(path!["a", "1"].indices().count(), 0);
+        assert_eq!(path!["a", "1"].keys().collect::<Vec<_>>(), vec!["a", "1"]);
+    }
+
+    #[test]
+    fn into_iter_works_for_mut_ref() {
+        let mut path = path!["userId", 2, "createdAt"];
+        for item in &mut path {
+            if let Some(key) = item.as_key_mut() {
+                *key = key.chars().flat_map(|c| {
+                    let lower = c.to_ascii_lowercase();
+                    (c.is_ascii_uppercase().then_some('_').into_iter()).chain([lower])
+                }).collect();
+            }
+        }
+        assert_eq!(path, path!["user_id", 2, "created_at"]);
+    }
+
+    #[test]
+    fn first_mut_and_last_mut_work() {
+        let mut path = path!["a", 1];
+        *path.last_mut().unwrap().as_index_mut().unwrap() += 1;
+        *path.first_mut().unwrap() = Item::Key("b".into());
+        assert_eq!(path, path!["b", 2]);
+        assert_eq!(KeyPath::default().last_mut(), None);
+        assert_eq!(path.iter_mut().rev().len(), 2);
     }
 
     #[test]
@@ -0,0 +1,92 @@
+use alloc::string::{String, ToString};
+use core::fmt::{Display, Formatter};
+use crate::{Item, KeyPath};
+
+/// Error returned by `KeyPath::to_mongo_key` for an item MongoDB dot notation
+/// can't hold.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MongoKeyError {
+    EmptyKey { position: usize },
+    DottedKey { position: usize, key: String },
+    DollarKey { position: usize, key: String },
+    NextIndex { position: usize },
+}
+
+impl MongoKeyError {
+
+    /// The position of the offending item in the path.
+    pub fn position(&self) -> usize {
+        use MongoKeyError::*;
+        match self {
+            EmptyKey { position } | DottedKey { position, .. } | DollarKey { position, .. } | NextIndex { position } => *position,
+        }
+    }
+}
+
+impl Display for MongoKeyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        use MongoKeyError::*;
+        match self {
+            EmptyKey { position } => write!(f, "empty key at position {} is not a valid field name", position),
+            DottedKey { position, key } => write!(f, "key `{}` at position {} contains `.`", key, position),
+            DollarKey { position, key } => write!(f, "key `{}` at position {} starts with `$`", key, position),
+            NextIndex { position } => write!(f, "next index at position {} has no dot notation", position),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MongoKeyError { }
+
+impl KeyPath {
+
+    /// Renders the path in MongoDB dot notation like `user.addresses.2.zip`.
+    ///
+    /// Keys that are empty, contain `.` or start with `$` would be read back
+    /// as something else, so they are rejected instead of being written out.
+    pub fn to_mongo_key(&self) -> Result<String, MongoKeyError> {
+        let mut result = String::new();
+        for (position, item) in self.iter().enumerate() {
+            if position > 0 {
+                result.push('.');
+            }
+            match item {
+                Item::Key(key) if key.is_empty() => return Err(MongoKeyError::EmptyKey { position }),
+                Item::Key(key) if key.contains('.') => return Err(MongoKeyError::DottedKey { position, key: key.to_string() }),
+                Item::Key(key) if key.starts_with('$') => return Err(MongoKeyError::DollarKey { position, key: key.to_string() }),
+                Item::Key(key) => result.push_str(key),
+                Item::Index(index) => result.push_str(&index.to_string()),
+                Item::NextIndex => return Err(MongoKeyError::NextIndex { position }),
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path;
+
+    #[test]
+    fn to_mongo_key_works() {
+        assert_eq!(path!["user", "addresses", 2, "zip"].to_mongo_key(), Ok("user.addresses.2.zip".to_owned()));
+        assert_eq!(path!["price$", "a-b"].to_mongo_key(), Ok("price$.a-b".to_owned()));
+        assert_eq!(KeyPath::default().to_mongo_key(), Ok("".to_owned()));
+    }
+
+    #[test]
+    fn to_mongo_key_rejects_unrepresentable_items() {
+        assert_eq!(path!["a", "b.c"].to_mongo_key(), Err(MongoKeyError::DottedKey { position: 1, key: "b.c".to_owned() }));
+        assert_eq!(path!["$set"].to_mongo_key(), Err(MongoKeyError::DollarKey { position: 0, key: "$set".to_owned() }));
+        assert_eq!(path!["a", 1, ""].to_mongo_key(), Err(MongoKeyError::EmptyKey { position: 2 }));
+        assert_eq!(KeyPath::new(vec![Item::Key("a".into()), Item::NextIndex]).to_mongo_key(), Err(MongoKeyError::NextIndex { position: 1 }));
+    }
+
+    #[test]
+    fn mongo_key_error_works() {
+        let error = path!["a", "b", "c.d"].to_mongo_key().unwrap_err();
+        assert_eq!(error.position(), 2);
+        assert_eq!(error.to_string(), "key `c.d` at position 2 contains `.`");
+    }
+}
@@ -0,0 +1,98 @@
+use core::fmt::{Display, Formatter};
+use crate::{Item, KeyPath};
+
+/// Error returned by `KeyPath::normalize_checked` when a `..` key would go
+/// above the root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AboveRootError {
+    position: usize,
+}
+
+impl AboveRootError {
+
+    /// The position of the offending `..` in the original path.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl Display for AboveRootError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "`..` at position {} goes above the root", self.position)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AboveRootError { }
+
+impl KeyPath {
+
+    /// Resolves relative markers: drops every `"."` key and lets every `".."`
+    /// key remove the item before it, so `path!["a", "b", "..", "c"]` becomes
+    /// `path!["a", "c"]`. A `..` with nothing left to remove is dropped, see
+    /// `normalize_checked`.
+    pub fn normalize(&self) -> KeyPath {
+        normalize(self, false).expect("`..` above the root is dropped")
+    }
+
+    /// Like `normalize`, but fails on a `..` that would go above the root.
+    pub fn normalize_checked(&self) -> Result<KeyPath, AboveRootError> {
+        normalize(self, true)
+    }
+}
+
+fn normalize(path: &KeyPath, strict: bool) -> Result<KeyPath, AboveRootError> {
+    let mut result = KeyPath::with_capacity(path.len());
+    for (position, item) in path.iter().enumerate() {
+        match item {
+            Item::Key(key) if key == "." => (),
+            Item::Key(key) if key == ".." => {
+                if result.pop().is_none() && strict {
+                    return Err(AboveRootError { position });
+                }
+            }
+            item => result.push(item.clone()),
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path;
+
+    #[test]
+    fn normalize_works() {
+        assert_eq!(path!["a", "b", "..", "c"].normalize(), path!["a", "c"]);
+        assert_eq!(path!["a", ".", 1, ".", "b"].normalize(), path!["a", 1, "b"]);
+        assert_eq!(path!["a", "...", "b"].normalize(), path!["a", "...", "b"]);
+    }
+
+    #[test]
+    fn normalize_works_for_trailing_parent() {
+        assert_eq!(path!["a", "b", ".."].normalize(), path!["a"]);
+        assert_eq!(path!["a", ".."].normalize(), KeyPath::default());
+    }
+
+    #[test]
+    fn normalize_works_for_consecutive_parents() {
+        assert_eq!(path!["a", "b", "c", "..", "..", "d"].normalize(), path!["a", "d"]);
+        assert_eq!(path!["a", 0, "..", "..", "b"].normalize(), path!["b"]);
+    }
+
+    #[test]
+    fn normalize_drops_parents_above_root() {
+        assert_eq!(path!["..", "a"].normalize(), path!["a"]);
+        assert_eq!(path!["a", "..", "..", "b"].normalize(), path!["b"]);
+    }
+
+    #[test]
+    fn normalize_checked_works() {
+        assert_eq!(path!["a", "b", "..", "c"].normalize_checked(), Ok(path!["a", "c"]));
+        let error = path!["a", "..", "..", "b"].normalize_checked().unwrap_err();
+        assert_eq!(error.position(), 2);
+        assert_eq!(error.to_string(), "`..` at position 2 goes above the root");
+        assert_eq!(path![".."].normalize_checked(), Err(AboveRootError { position: 0 }));
+    }
+}
@@ -0,0 +1,553 @@
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+use crate::{escape_key, Item, KeyPath};
+use KeyPathParseErrorKind::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyPathParseErrorKind {
+    DepthExceeded,
+    EmptySegment,
+    IndexOverflow,
+    InvalidEscape,
+    InvalidIndex,
+    InvalidUtf8,
+    UnclosedBracket,
+    UnclosedQuote,
+    UnexpectedChar,
+}
+
+/// Error returned by the `KeyPath` parsers, carrying the byte offset into the
+/// input where parsing failed and the slice of input that caused it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyPathParseError {
+    kind: KeyPathParseErrorKind,
+    offset: usize,
+    fragment: String,
+}
+
+impl KeyPathParseError {
+
+    pub(crate) fn new(kind: KeyPathParseErrorKind, offset: usize, fragment: impl Into<String>) -> Self {
+        Self { kind, offset, fragment: fragment.into() }
+    }
+
+    pub fn kind(&self) -> KeyPathParseErrorKind {
+        self.kind
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn fragment(&self) -> &str {
+        &self.fragment
+    }
+}
+
+impl Display for KeyPathParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        use KeyPathParseErrorKind::*;
+        let Self { kind, offset, fragment } = self;
+        match kind {
+            DepthExceeded => write!(f, "path exceeds the maximum depth at offset {}", offset),
+            EmptySegment => write!(f, "empty segment at offset {}", offset),
+            IndexOverflow => write!(f, "index `{}` at offset {} overflows usize", fragment, offset),
+            InvalidEscape => write!(f, "invalid escape `{}` at offset {}", fragment, offset),
+            InvalidIndex => write!(f, "invalid index `{}` at offset {}", fragment, offset),
+            InvalidUtf8 => write!(f, "invalid UTF-8 `{}` at offset {}", fragment, offset),
+            UnclosedBracket => write!(f, "unclosed bracket at offset {}", offset),
+            UnclosedQuote => write!(f, "unclosed quote at offset {}", offset),
+            UnexpectedChar => write!(f, "unexpected `{}` at offset {}", fragment, offset),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for KeyPathParseError { }
+
+/// Options for `KeyPath::parse_with`. The defaults match `FromStr`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseOptions {
+    numeric_as_index: bool,
+    allow_empty_segments: bool,
+    max_depth: Option<usize>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self { numeric_as_index: true, allow_empty_segments: false, max_depth: None }
+    }
+}
+
+impl ParseOptions {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether unescaped digit segments become indices and an unescaped `-`
+    /// becomes `Item::NextIndex`. When `false`, every segment is a key.
+    pub fn numeric_as_index(mut self, numeric_as_index: bool) -> Self {
+        self.numeric_as_index = numeric_as_index;
+        self
+    }
+
+    /// Whether empty segments like the middle one in `a..b` become empty keys
+    /// instead of an error.
+    pub fn allow_empty_segments(mut self, allow_empty_segments: bool) -> Self {
+        self.allow_empty_segments = allow_empty_segments;
+        self
+    }
+
+    /// The maximum number of items; longer input fails with
+    /// `KeyPathParseErrorKind::DepthExceeded` before more items are allocated.
+    pub fn max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+}
+
+/// Parses the dotted form produced by `Display`, see `KeyPath::parse_escaped`.
+/// `Display` doesn't escape keys made of digits, so those read back as
+/// indices; use `to_escaped_string` for output that reads back exactly.
+///
+/// A segment consisting entirely of unescaped ASCII digits becomes an
+/// `Item::Index` and an unescaped `-` becomes `Item::NextIndex`, any other
+/// segment becomes an `Item::Key`. This means `"3"` parses into `path![3]`,
+/// never `path!["3"]`. An empty string parses into the empty path.
+impl FromStr for KeyPath {
+    type Err = KeyPathParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_escaped(s)
+    }
+}
+
+impl KeyPath {
+
+    pub fn parse(s: &str) -> Result<Self, KeyPathParseError> {
+        s.parse()
+    }
+
+    /// Parses bytes in `FromStr` syntax. Invalid UTF-8 fails with
+    /// `KeyPathParseErrorKind::InvalidUtf8` at the offset of the first bad
+    /// sequence, whose bytes make up the fragment as `\xNN` escapes. Offsets
+    /// of grammar errors are byte offsets into `bytes` too.
+    pub fn from_utf8(bytes: &[u8]) -> Result<Self, KeyPathParseError> {
+        let s = core::str::from_utf8(bytes).map_err(|error| {
+            let offset = error.valid_up_to();
+            let len = error.error_len().unwrap_or(bytes.len() - offset);
+            let fragment: String = bytes[offset..offset + len].iter().map(|b| format!("\\x{:02x}", b)).collect();
+            KeyPathParseError::new(InvalidUtf8, offset, fragment)
+        })?;
+        s.parse()
+    }
+
+    /// Like `from_utf8`, but replaces invalid sequences with U+FFFD. As the
+    /// replacement is three bytes long, later error offsets may not line up
+    /// with `bytes`.
+    pub fn from_utf8_lossy(bytes: &[u8]) -> Result<Self, KeyPathParseError> {
+        String::from_utf8_lossy(bytes).parse()
+    }
+
+    /// Builds a path of keys, one per segment, without interpreting digits.
+    pub fn from_segments<I, S>(segments: I) -> Self where I: IntoIterator<Item = S>, S: AsRef<str> {
+        segments.into_iter().map(|segment| Item::Key(segment.as_ref().to_owned().into())).collect()
+    }
+
+    /// Like `from_segments`, but segments that are canonical decimal numbers
+    /// become indices. Numbers with leading zeros like `"007"` and ones that
+    /// overflow `usize` stay keys, so no segment is changed when read back.
+    pub fn from_segments_detect<I, S>(segments: I) -> Self where I: IntoIterator<Item = S>, S: AsRef<str> {
+        segments.into_iter().map(|segment| {
+            let segment = segment.as_ref();
+            let canonical = segment == "0" || (!segment.is_empty() && !segment.starts_with('0') && segment.bytes().all(|b| b.is_ascii_digit()));
+            match segment.parse() {
+                Ok(index) if canonical => Item::Index(index),
+                _ => Item::Key(segment.to_owned().into()),
+            }
+        }).collect()
+    }
+
+    /// Splits `s` on `sep`, the reverse of `join_with`.
+    ///
+    /// Segments made of ASCII digits become indices, as in `FromStr`, except
+    /// that ones overflowing `usize` stay keys. Empty segments become empty
+    /// keys and an empty string becomes the empty path.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sep` is empty.
+    pub fn split_str(s: &str, sep: &str) -> Self {
+        assert!(!sep.is_empty(), "separator must not be empty");
+        if s.is_empty() {
+            return KeyPath::default();
+        }
+        KeyPath::new(s.split(sep).map(|segment| parse_segment(segment, 0).unwrap_or_else(|_| Item::Key(segment.to_owned().into()))).collect())
+    }
+
+    /// Parses the form produced by `to_escaped_string`.
+    ///
+    /// A backslash makes the following character literal, so `\.` stays inside
+    /// the key and `\\` is a literal backslash. A segment containing an escape
+    /// is always a key, even if it is made of digits.
+    pub fn parse_escaped(s: &str) -> Result<Self, KeyPathParseError> {
+        Self::parse_with(s, &ParseOptions::default())
+    }
+
+    /// Parses the same syntax as `parse_escaped`, configured by `options`.
+    pub fn parse_with(s: &str, options: &ParseOptions) -> Result<Self, KeyPathParseError> {
+        if s.is_empty() {
+            return Ok(KeyPath::default());
+        }
+        let mut items = vec![];
+        let mut segment = String::new();
+        let mut escaped = false;
+        let mut start = 0;
+        let mut chars = s.char_indices();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some((_, c)) => {
+                        segment.push(c);
+                        escaped = true;
+                    }
+                    None => return Err(KeyPathParseError::new(InvalidEscape, offset, "\\")),
+                },
+                '.' => {
+                    push_segment(&mut items, s, segment, escaped, start, options)?;
+                    segment = String::new();
+                    escaped = false;
+                    start = offset + 1;
+                }
+                c => segment.push(c),
+            }
+        }
+        push_segment(&mut items, s, segment, escaped, start, options)?;
+        Ok(KeyPath::new(items))
+    }
+
+    /// Renders the path with `.` and `\` inside keys backslash-escaped.
+    ///
+    /// Like `Display`, but keys made of digits get their first digit escaped
+    /// so that they are not read back as indices. The output parses back into
+    /// the same path unless the path holds an empty key, which has no
+    /// representation.
+    pub fn to_escaped_string(&self) -> String {
+        let mut result = String::new();
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                result.push('.');
+            }
+            match item {
+                Item::Key(key) => {
+                    if !key.is_empty() && key.bytes().all(|b| b.is_ascii_digit()) {
+                        result.push('\\');
+                    }
+                    result.push_str(&escape_key(key));
+                }
+                Item::Index(index) => result.push_str(&index.to_string()),
+                Item::NextIndex => result.push('-'),
+            }
+        }
+        result
+    }
+}
+
+pub(crate) fn unexpected_char(s: &str, offset: usize) -> KeyPathParseError {
+    let found = s[offset..].chars().next().unwrap();
+    KeyPathParseError::new(UnexpectedChar, offset, found)
+}
+
+fn push_segment(items: &mut Vec<Item>, s: &str, segment: String, escaped: bool, offset: usize, options: &ParseOptions) -> Result<(), KeyPathParseError> {
+    if options.max_depth == Some(items.len()) {
+        return Err(KeyPathParseError::new(DepthExceeded, offset, &s[offset..]));
+    }
+    let item = if segment.is_empty() && !options.allow_empty_segments {
+        return Err(KeyPathParseError::new(EmptySegment, offset, ""));
+    } else if escaped || segment.is_empty() || !options.numeric_as_index {
+        Item::Key(segment.into())
+    } else if segment == "-" {
+        Item::NextIndex
+    } else {
+        parse_segment(&segment, offset)?
+    };
+    items.push(item);
+    Ok(())
+}
+
+fn parse_segment(segment: &str, offset: usize) -> Result<Item, KeyPathParseError> {
+    if segment.is_empty() {
+        return Err(KeyPathParseError::new(EmptySegment, offset, ""));
+    }
+    if segment.bytes().all(|b| b.is_ascii_digit()) {
+        segment.parse().map(Item::Index).map_err(|_| KeyPathParseError::new(IndexOverflow, offset, segment))
+    } else {
+        Ok(Item::Key(segment.to_owned().into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path;
+
+    #[test]
+    fn parse_works() {
+        let result: KeyPath = "where.items.5.name".parse().unwrap();
+        assert_eq!(result, path!["where", "items", 5, "name"]);
+    }
+
+    #[test]
+    fn parse_works_for_digit_segments() {
+        let result: KeyPath = "a.2.3".parse().unwrap();
+        assert_eq!(result, path!["a", 2, 3]);
+        let result: KeyPath = "3".parse().unwrap();
+        assert_eq!(result, path![3]);
+    }
+
+    #[test]
+    fn parse_works_for_empty() {
+        let result: KeyPath = "".parse().unwrap();
+        assert_eq!(result, KeyPath::default());
+    }
+
+    #[test]
+    fn parse_errors_on_empty_segment() {
+        let result = "a..b".parse::<KeyPath>();
+        assert_eq!(result, Err(KeyPathParseError::new(EmptySegment, 2, "")));
+    }
+
+    #[test]
+    fn parse_errors_on_trailing_dot() {
+        let result = KeyPath::parse("a.b.");
+        assert_eq!(result, Err(KeyPathParseError::new(EmptySegment, 4, "")));
+    }
+
+    #[test]
+    fn parse_errors_on_leading_dot() {
+        let result = KeyPath::parse(".a");
+        assert_eq!(result, Err(KeyPathParseError::new(EmptySegment, 0, "")));
+    }
+
+    #[test]
+    fn parse_works_for_unicode_keys() {
+        let result = KeyPath::parse("用户.地址.0.città").unwrap();
+        assert_eq!(result, path!["用户", "地址", 0, "città"]);
+    }
+
+    #[test]
+    fn parse_keeps_mixed_digit_segments_as_keys() {
+        let result = KeyPath::parse("a1.1a.-1").unwrap();
+        assert_eq!(result, path!["a1", "1a", "-1"]);
+    }
+
+    #[test]
+    fn parse_round_trips_display() {
+        let path = path!["where", "items", 5, "name"];
+        assert_eq!(KeyPath::parse(&path.to_string()).unwrap(), path);
+    }
+
+    #[test]
+    fn parse_round_trips_display_of_dash_key_and_next_index() {
+        let path = path!["items", "-", Item::NextIndex, "-x"];
+        assert_eq!(path.to_string(), r"items.\-.-.-x");
+        assert_eq!(KeyPath::parse(&path.to_string()).unwrap(), path);
+    }
+
+    #[test]
+    fn from_utf8_works() {
+        assert_eq!(KeyPath::from_utf8(b"user.addresses.2"), Ok(path!["user", "addresses", 2]));
+        assert_eq!(KeyPath::from_utf8("café.ü".as_bytes()), Ok(path!["café", "ü"]));
+        assert_eq!(KeyPath::from_utf8(b""), Ok(KeyPath::default()));
+    }
+
+    #[test]
+    fn from_utf8_reports_invalid_sequences() {
+        let error = KeyPath::from_utf8(b"ab.c\xffd").unwrap_err();
+        assert_eq!(error, KeyPathParseError::new(InvalidUtf8, 4, "\\xff"));
+        assert_eq!(error.to_string(), "invalid UTF-8 `\\xff` at offset 4");
+        assert_eq!(KeyPath::from_utf8(b"a.\xe6\x97"), Err(KeyPathParseError::new(InvalidUtf8, 2, "\\xe6\\x97")));
+    }
+
+    #[test]
+    fn from_utf8_reports_grammar_errors_at_byte_offsets() {
+        assert_eq!(KeyPath::from_utf8("é..b".as_bytes()), Err(KeyPathParseError::new(EmptySegment, 3, "")));
+    }
+
+    #[test]
+    fn from_utf8_lossy_works() {
+        assert_eq!(KeyPath::from_utf8_lossy(b"a.b\xffc.1"), Ok(path!["a", "b\u{fffd}c", 1]));
+        assert_eq!(KeyPath::from_utf8_lossy(b"a..b").unwrap_err().kind(), EmptySegment);
+    }
+
+    #[test]
+    fn from_segments_works() {
+        assert_eq!(KeyPath::from_segments(["users", "3", "email"]), path!["users", "3", "email"]);
+        assert_eq!(KeyPath::from_segments(vec![String::from("a"), String::from("")]), path!["a", ""]);
+        assert_eq!(KeyPath::from_segments(Vec::<String>::new()), KeyPath::default());
+    }
+
+    #[test]
+    fn from_segments_detect_works() {
+        assert_eq!(KeyPath::from_segments_detect(["users", "3", "email", "0"]), path!["users", 3, "email", 0]);
+        assert_eq!(KeyPath::from_segments_detect(vec![String::from("10")]), path![10]);
+    }
+
+    #[test]
+    fn from_segments_detect_keeps_non_canonical_numbers_as_keys() {
+        assert_eq!(KeyPath::from_segments_detect(["007", "00", "", "+1", "-1", "1.5"]), path!["007", "00", "", "+1", "-1", "1.5"]);
+        assert_eq!(KeyPath::from_segments_detect(["99999999999999999999999"]), path!["99999999999999999999999"]);
+    }
+
+    #[test]
+    fn parse_escaped_works() {
+        let result = KeyPath::parse_escaped(r"user\.name.first").unwrap();
+        assert_eq!(result, path!["user.name", "first"]);
+    }
+
+    #[test]
+    fn parse_escaped_works_for_backslash() {
+        let result = KeyPath::parse_escaped(r"a\\b.2").unwrap();
+        assert_eq!(result, path![r"a\b", 2]);
+    }
+
+    #[test]
+    fn parse_escaped_keeps_escaped_digits_as_key() {
+        let result = KeyPath::parse_escaped(r"a.\12.12").unwrap();
+        assert_eq!(result, path!["a", "12", 12]);
+    }
+
+    #[test]
+    fn parse_escaped_errors_on_trailing_backslash() {
+        let result = KeyPath::parse_escaped(r"a.b\");
+        assert_eq!(result, Err(KeyPathParseError::new(InvalidEscape, 3, r"\")));
+    }
+
+    #[test]
+    fn parse_escaped_errors_on_empty_segment() {
+        let result = KeyPath::parse_escaped(r"a\..");
+        assert_eq!(result, Err(KeyPathParseError::new(EmptySegment, 4, "")));
+    }
+
+    #[test]
+    fn to_escaped_string_works() {
+        let path = path!["user.name", r"back\slash", 3, "3"];
+        assert_eq!(path.to_escaped_string(), r"user\.name.back\\slash.3.\3");
+    }
+
+    #[test]
+    fn escaped_string_round_trips() {
+        let keys = ["a", ".", "..", r"\", r"\.", r".\", "a.b.c", r"a\b\c", "0", "007", "1.5", "-", "--", r"\-", "ü.ö"];
+        for key in keys {
+            for path in [path![key], path![key, 1, key], path![0, key, "x"], path![key, Item::NextIndex]] {
+                assert_eq!(KeyPath::parse_escaped(&path.to_escaped_string()).unwrap(), path);
+            }
+        }
+        let path = path![Item::NextIndex, "-", Item::NextIndex];
+        assert_eq!(path.to_escaped_string(), r"-.\-.-");
+        assert_eq!(KeyPath::parse_escaped(&path.to_escaped_string()).unwrap(), path);
+    }
+
+    #[test]
+    fn to_escaped_string_cannot_represent_empty_keys() {
+        assert_eq!(path!["a", ""].to_escaped_string(), "a.");
+        assert!(KeyPath::parse_escaped(&path!["a", ""].to_escaped_string()).is_err());
+        assert_eq!(KeyPath::parse_escaped(&path![""].to_escaped_string()).unwrap(), KeyPath::default());
+    }
+
+    #[test]
+    fn parse_reads_dash_as_next_index() {
+        assert_eq!(KeyPath::parse("items.-").unwrap(), path!["items", Item::NextIndex]);
+        assert_eq!(KeyPath::parse(r"items.\-").unwrap(), path!["items", "-"]);
+        assert_eq!(KeyPath::parse("items.--.-1").unwrap(), path!["items", "--", "-1"]);
+        assert_eq!(KeyPath::parse_with("items.-", &ParseOptions::new().numeric_as_index(false)).unwrap(), path!["items", "-"]);
+    }
+
+    #[test]
+    fn parse_round_trips_keys_with_dots_and_backslashes() {
+        let path = path!["a.b", r"c\d", 1, r"\."];
+        assert_eq!(path.to_string().parse::<KeyPath>().unwrap(), path);
+    }
+
+    #[test]
+    fn split_str_works() {
+        let result = KeyPath::split_str("DATABASE__POOL__0__URL", "__");
+        assert_eq!(result, path!["DATABASE", "POOL", 0, "URL"]);
+        let result = KeyPath::split_str("a/b/2", "/");
+        assert_eq!(result, path!["a", "b", 2]);
+    }
+
+    #[test]
+    fn split_str_works_for_edge_cases() {
+        assert_eq!(KeyPath::split_str("", "::"), KeyPath::default());
+        assert_eq!(KeyPath::split_str("a::::b::", "::"), path!["a", "", "b", ""]);
+        assert_eq!(KeyPath::split_str("a/99999999999999999999999", "/"), path!["a", "99999999999999999999999"]);
+    }
+
+    #[test]
+    fn split_str_round_trips_join_with() {
+        let path = path!["database", "pool", 0, "url"];
+        assert_eq!(KeyPath::split_str(&path.join_with("__"), "__"), path);
+    }
+
+    #[test]
+    fn parse_with_default_options_matches_parse() {
+        let result = KeyPath::parse_with(r"a.1.b\.c", &ParseOptions::default());
+        assert_eq!(result, KeyPath::parse(r"a.1.b\.c"));
+    }
+
+    #[test]
+    fn parse_with_keys_only() {
+        let options = ParseOptions::new().numeric_as_index(false);
+        let result = KeyPath::parse_with("a.0.99999999999999999999999", &options).unwrap();
+        assert_eq!(result, path!["a", "0", "99999999999999999999999"]);
+        assert_eq!(KeyPath::parse_with("a..0", &options), Err(KeyPathParseError::new(EmptySegment, 2, "")));
+    }
+
+    #[test]
+    fn parse_with_empty_segments() {
+        let options = ParseOptions::new().allow_empty_segments(true);
+        let result = KeyPath::parse_with(".a..1.", &options).unwrap();
+        assert_eq!(result, path!["", "a", "", 1, ""]);
+    }
+
+    #[test]
+    fn parse_with_max_depth() {
+        let options = ParseOptions::new().max_depth(Some(2));
+        assert_eq!(KeyPath::parse_with("a.b", &options).unwrap(), path!["a", "b"]);
+        let result = KeyPath::parse_with("a.b.c.d", &options);
+        assert_eq!(result, Err(KeyPathParseError::new(DepthExceeded, 4, "c.d")));
+        let options = ParseOptions::new().max_depth(Some(0));
+        assert_eq!(KeyPath::parse_with("", &options).unwrap(), KeyPath::default());
+    }
+
+    #[test]
+    fn parse_errors_on_index_overflow() {
+        let result = "a.99999999999999999999999".parse::<KeyPath>();
+        assert_eq!(result, Err(KeyPathParseError::new(IndexOverflow, 2, "99999999999999999999999")));
+        assert_eq!(result.unwrap_err().to_string(), "index `99999999999999999999999` at offset 2 overflows usize");
+    }
+
+    #[test]
+    fn parse_error_accessors_work() {
+        let error = KeyPath::parse("a.b..c").unwrap_err();
+        assert_eq!(error.kind(), EmptySegment);
+        assert_eq!(error.offset(), 4);
+        assert_eq!(error.fragment(), "");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn parse_error_can_be_boxed() {
+        fn parse(s: &str) -> Result<KeyPath, Box<dyn std::error::Error>> {
+            Ok(s.parse()?)
+        }
+        assert_eq!(parse("a.").unwrap_err().to_string(), "empty segment at offset 2");
+    }
+}
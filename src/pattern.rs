@@ -0,0 +1,135 @@
+use alloc::vec::Vec;
+use core::str::FromStr;
+use crate::{Item, KeyPath, KeyPathParseError};
+
+/// A segment of a `Pattern`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PatternSegment {
+    /// Matches exactly this item.
+    Item(Item),
+    /// `*`, matches any single item.
+    Wildcard,
+    /// `**`, matches any run of items, including none.
+    DoubleWildcard,
+}
+
+/// A path pattern like `users.*.email`, matched against whole paths.
+///
+/// Patterns use the `FromStr` syntax of `KeyPath`, with an unescaped `*`
+/// segment matching any one item and an unescaped `**` segment matching zero
+/// or more items. Write `\*` for a literal `*` key.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct Pattern {
+    segments: Vec<PatternSegment>,
+}
+
+impl Pattern {
+
+    pub fn new(segments: Vec<PatternSegment>) -> Self {
+        Self { segments }
+    }
+
+    pub fn parse(s: &str) -> Result<Self, KeyPathParseError> {
+        let items = KeyPath::parse(s)?;
+        let segments = raw_segments(s).zip(items).map(|(raw, item)| match raw {
+            "*" => PatternSegment::Wildcard,
+            "**" => PatternSegment::DoubleWildcard,
+            _ => PatternSegment::Item(item),
+        }).collect();
+        Ok(Self { segments })
+    }
+
+    pub fn segments(&self) -> &[PatternSegment] {
+        &self.segments
+    }
+
+    pub fn matches(&self, path: &KeyPath) -> bool {
+        matches_from(&self.segments, path.as_slice())
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = KeyPathParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// The source text of each segment of an already validated path.
+fn raw_segments(s: &str) -> impl Iterator<Item = &str> {
+    let mut escaped = false;
+    s.split(move |c| {
+        let split = !escaped && c == '.';
+        escaped = !escaped && c == '\\';
+        split
+    }).filter(move |_| !s.is_empty())
+}
+
+fn matches_from(segments: &[PatternSegment], items: &[Item]) -> bool {
+    match segments.split_first() {
+        None => items.is_empty(),
+        Some((PatternSegment::DoubleWildcard, rest)) => (0..=items.len()).any(|skip| matches_from(rest, &items[skip..])),
+        Some((segment, rest)) => match items.split_first() {
+            Some((item, items)) => (*segment == PatternSegment::Wildcard || *segment == PatternSegment::Item(item.clone())) && matches_from(rest, items),
+            None => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path;
+
+    #[test]
+    fn parse_works() {
+        let pattern = Pattern::parse(r"users.*.**.3.\*").unwrap();
+        assert_eq!(pattern.segments(), &[
+            PatternSegment::Item(Item::Key("users".into())),
+            PatternSegment::Wildcard,
+            PatternSegment::DoubleWildcard,
+            PatternSegment::Item(Item::Index(3)),
+            PatternSegment::Item(Item::Key("*".into())),
+        ]);
+        assert_eq!("a..b".parse::<Pattern>(), Err("a..b".parse::<KeyPath>().unwrap_err()));
+    }
+
+    #[test]
+    fn wildcard_matches_one_item() {
+        let pattern = Pattern::parse("users.*.email").unwrap();
+        assert!(pattern.matches(&path!["users", 3, "email"]));
+        assert!(pattern.matches(&path!["users", "admin", "email"]));
+        assert!(!pattern.matches(&path!["users", "email"]));
+        assert!(!pattern.matches(&path!["users", 3, "x", "email"]));
+    }
+
+    #[test]
+    fn double_wildcard_matches_any_run() {
+        let pattern = Pattern::parse("users.**.email").unwrap();
+        assert!(pattern.matches(&path!["users", "email"]));
+        assert!(pattern.matches(&path!["users", 3, "email"]));
+        assert!(pattern.matches(&path!["users", 3, "x", "email"]));
+        assert!(!pattern.matches(&path!["users", 3, "email", "x"]));
+        assert!(!pattern.matches(&path!["groups", 3, "email"]));
+    }
+
+    #[test]
+    fn trailing_wildcards_work() {
+        assert!(Pattern::parse("a.*").unwrap().matches(&path!["a", 1]));
+        assert!(!Pattern::parse("a.*").unwrap().matches(&path!["a"]));
+        assert!(Pattern::parse("a.**").unwrap().matches(&path!["a"]));
+        assert!(Pattern::parse("a.**").unwrap().matches(&path!["a", 1, "b"]));
+        assert!(Pattern::parse("**").unwrap().matches(&KeyPath::default()));
+    }
+
+    #[test]
+    fn literals_must_match_exactly() {
+        let pattern = Pattern::parse(r"items.0.\*").unwrap();
+        assert!(pattern.matches(&path!["items", 0, "*"]));
+        assert!(!pattern.matches(&path!["items", "0", "*"]));
+        assert!(!pattern.matches(&path!["items", 0, "x"]));
+        assert!(Pattern::parse("").unwrap().matches(&KeyPath::default()));
+        assert!(!Pattern::parse("").unwrap().matches(&path!["a"]));
+    }
+}
@@ -0,0 +1,84 @@
+use alloc::string::String;
+use proptest::arbitrary::{any, Arbitrary};
+use proptest::collection::vec;
+use proptest::prelude::{prop_oneof, Just};
+use proptest::strategy::{BoxedStrategy, Strategy};
+use crate::{Item, KeyPath};
+
+/// Parameters for generating a `KeyPath` with `any_with`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyPathParameters {
+    max_depth: usize,
+}
+
+impl Default for KeyPathParameters {
+    fn default() -> Self {
+        Self { max_depth: 8 }
+    }
+}
+
+impl KeyPathParameters {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The largest number of items generated, 8 by default.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+}
+
+/// Generates mostly short keys and indices. Keys include the edge cases of
+/// the string syntaxes: empty keys, keys made of digits and keys containing
+/// `.`, `\` and other punctuation. `Item::NextIndex` is generated rarely.
+impl Arbitrary for Item {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Item>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        let key = prop_oneof![
+            1 => Just(String::new()),
+            6 => "[a-zA-Z_][a-zA-Z0-9_]{0,11}",
+            3 => r"[a-z0-9.\\\-~/\[\]]{1,8}",
+            1 => "[0-9]{1,3}",
+            2 => r"\PC{1,16}",
+        ];
+        prop_oneof![
+            6 => key.prop_map(|key| Item::Key(key.into())),
+            3 => any::<usize>().prop_map(Item::Index),
+            1 => Just(Item::NextIndex),
+        ].boxed()
+    }
+}
+
+impl Arbitrary for KeyPath {
+    type Parameters = KeyPathParameters;
+    type Strategy = BoxedStrategy<KeyPath>;
+
+    fn arbitrary_with(parameters: Self::Parameters) -> Self::Strategy {
+        vec(any::<Item>(), 0..=parameters.max_depth).prop_map(KeyPath::new).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn escaped_string_round_trips(path in any::<KeyPath>()) {
+            // `Display` doesn't escape keys made of digits, `to_escaped_string`
+            // does. Empty keys have no representation.
+            prop_assume!(path.iter().all(|item| item.as_key() != Some("")));
+            prop_assert_eq!(path.to_escaped_string().parse::<KeyPath>().unwrap(), path);
+        }
+
+        #[test]
+        fn generated_paths_respect_max_depth(path in any_with::<KeyPath>(KeyPathParameters::new().max_depth(2))) {
+            prop_assert!(path.len() <= 2);
+        }
+    }
+}
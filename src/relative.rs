@@ -0,0 +1,303 @@
+use alloc::string::String;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+use crate::{Item, KeyPath, KeyPathParseError, KeyPathParseErrorKind::*};
+use crate::json_pointer::{parse_tokens, push_token};
+use crate::parse::unexpected_char;
+
+/// A Relative JSON Pointer like `1/foo` or `0#`: a number of levels to go up,
+/// an optional index adjustment like `+2`, then either a path to descend into
+/// or `#` for the key of the location reached.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RelativeKeyPath {
+    up: usize,
+    index_offset: isize,
+    target: Option<KeyPath>,
+}
+
+impl RelativeKeyPath {
+
+    /// How many items to remove from the end of the base path.
+    pub fn up(&self) -> usize {
+        self.up
+    }
+
+    /// How much to add to the index the base path ends with after going up.
+    pub fn index_offset(&self) -> isize {
+        self.index_offset
+    }
+
+    /// The path to descend into, or `None` for the `#` form.
+    pub fn path(&self) -> Option<&KeyPath> {
+        self.target.as_ref()
+    }
+
+    pub fn is_key_reference(&self) -> bool {
+        self.target.is_none()
+    }
+}
+
+impl FromStr for RelativeKeyPath {
+    type Err = KeyPathParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.bytes().take_while(u8::is_ascii_digit).count();
+        let up = parse_number(s, 0, digits)?;
+        let mut pos = digits;
+        let mut index_offset = 0;
+        if let Some(sign @ (b'+' | b'-')) = s.as_bytes().get(pos).copied() {
+            let digits = s[pos + 1..].bytes().take_while(u8::is_ascii_digit).count();
+            let offset = parse_number(s, pos + 1, digits)?;
+            let offset = isize::try_from(offset).map_err(|_| KeyPathParseError::new(IndexOverflow, pos + 1, &s[pos + 1..pos + 1 + digits]))?;
+            index_offset = if sign == b'+' { offset } else { -offset };
+            pos += 1 + digits;
+        }
+        let rest = &s[pos..];
+        let target = if rest == "#" {
+            None
+        } else if rest.is_empty() || rest.starts_with('/') {
+            Some(KeyPath::new(parse_tokens(rest, pos)?))
+        } else {
+            return Err(unexpected_char(s, pos));
+        };
+        Ok(Self { up, index_offset, target })
+    }
+}
+
+/// Parses the `len` digits at `start` as a number without leading zeros.
+fn parse_number(s: &str, start: usize, len: usize) -> Result<usize, KeyPathParseError> {
+    let digits = &s[start..start + len];
+    if digits.is_empty() {
+        return Err(if s[start..].is_empty() {
+            KeyPathParseError::new(EmptySegment, start, "")
+        } else {
+            unexpected_char(s, start)
+        });
+    }
+    if digits.len() > 1 && digits.starts_with('0') {
+        return Err(KeyPathParseError::new(InvalidIndex, start, digits));
+    }
+    digits.parse().map_err(|_| KeyPathParseError::new(IndexOverflow, start, digits))
+}
+
+impl Display for RelativeKeyPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.up)?;
+        if self.index_offset != 0 {
+            write!(f, "{:+}", self.index_offset)?;
+        }
+        match &self.target {
+            None => f.write_str("#"),
+            Some(path) => {
+                let mut s = String::new();
+                for item in path {
+                    s.push('/');
+                    push_token(&mut s, item);
+                }
+                f.write_str(&s)
+            }
+        }
+    }
+}
+
+/// Error returned when a `RelativeKeyPath` can't be applied to a path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RelativePathError {
+    /// Going up would leave the root, as with `3/a` on a path of 2 items.
+    AboveRoot { up: usize, len: usize },
+    /// An index adjustment was given, but the location reached doesn't end
+    /// with an index.
+    NotAnIndex,
+    /// The adjusted index would be negative or overflow.
+    IndexOutOfRange,
+    /// The `#` form was applied at the root, which has no key.
+    NoKeyAtRoot,
+}
+
+impl Display for RelativePathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        use RelativePathError::*;
+        match self {
+            AboveRoot { up, len } => write!(f, "can't go up {} levels from a path of {} items", up, len),
+            NotAnIndex => f.write_str("index adjustment applied to a location that isn't an array element"),
+            IndexOutOfRange => f.write_str("adjusted index is out of range"),
+            NoKeyAtRoot => f.write_str("the root has no key"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RelativePathError { }
+
+/// Error returned by `KeyPath::relative_to` when the base is not a prefix of
+/// the path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NotAPrefixError {
+    position: usize,
+}
+
+impl NotAPrefixError {
+
+    /// The position of the first item of the base that the path doesn't have.
+    /// This is the path's length if the base is longer than it.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl Display for NotAPrefixError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "path diverges from the base at position {}", self.position)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NotAPrefixError { }
+
+impl KeyPath {
+
+    /// Parses a Relative JSON Pointer, see `RelativeKeyPath`.
+    pub fn parse_relative(s: &str) -> Result<RelativeKeyPath, KeyPathParseError> {
+        s.parse()
+    }
+
+    /// Resolves `relative` against this path: removes `up` items, adjusts the
+    /// last index, then appends the relative path. For the `#` form this is
+    /// the location whose key `relative_key` returns.
+    pub fn apply_relative(&self, relative: &RelativeKeyPath) -> Result<KeyPath, RelativePathError> {
+        let len = self.len().checked_sub(relative.up).ok_or(RelativePathError::AboveRoot { up: relative.up, len: self.len() })?;
+        let mut result = self.take(len);
+        if relative.index_offset != 0 {
+            let index = result.items.last_mut().and_then(Item::as_index_mut).ok_or(RelativePathError::NotAnIndex)?;
+            *index = index.checked_add_signed(relative.index_offset).ok_or(RelativePathError::IndexOutOfRange)?;
+        }
+        if let Some(path) = &relative.target {
+            result.items.extend(path.items.iter().cloned());
+        }
+        Ok(result)
+    }
+
+    /// Resolves the `#` form of `relative`, returning the key or index at
+    /// which the location reached sits in its parent. For other forms this is
+    /// the last item of `apply_relative`'s result.
+    pub fn relative_key(&self, relative: &RelativeKeyPath) -> Result<Item, RelativePathError> {
+        self.apply_relative(relative)?.pop().ok_or(RelativePathError::NoKeyAtRoot)
+    }
+
+    /// Returns the rest of the path after `base`, like `strip_prefix`, but
+    /// fails with the position where the two diverge. Call `.ok()` on the
+    /// result for an `Option`.
+    ///
+    /// Unlike relative filesystem paths there is no `..`: a path outside
+    /// `base` is an error, not a way up and over. See `RelativeKeyPath` for
+    /// that.
+    pub fn relative_to(&self, base: &KeyPath) -> Result<KeyPath, NotAPrefixError> {
+        let position = self.common_prefix_len(base);
+        if position < base.len() {
+            return Err(NotAPrefixError { position });
+        }
+        Ok(KeyPath::from(&self.items[position..]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path;
+
+    fn relative(s: &str) -> RelativeKeyPath {
+        KeyPath::parse_relative(s).unwrap()
+    }
+
+    #[test]
+    fn parse_relative_works() {
+        let rel = relative("1/foo/0");
+        assert_eq!(rel.up(), 1);
+        assert_eq!(rel.index_offset(), 0);
+        assert_eq!(rel.path(), Some(&path!["foo", 0]));
+        assert!(relative("0#").is_key_reference());
+        assert_eq!(relative("2-1/a~1b~0c").path(), Some(&path!["a/b~c"]));
+        assert_eq!(relative("2-1/a").index_offset(), -1);
+        assert_eq!(relative("0+3").index_offset(), 3);
+        assert_eq!(relative("0").path(), Some(&KeyPath::default()));
+    }
+
+    #[test]
+    fn parse_relative_works_for_empty_and_odd_tokens() {
+        assert_eq!(relative("0/").path(), Some(&path![""]));
+        assert_eq!(relative("0/01/-").path(), Some(&KeyPath::new(vec![Item::Key("01".into()), Item::NextIndex])));
+    }
+
+    #[test]
+    fn parse_relative_errors_on_invalid_input() {
+        assert_eq!(KeyPath::parse_relative(""), Err(KeyPathParseError::new(EmptySegment, 0, "")));
+        assert_eq!(KeyPath::parse_relative("/a"), Err(KeyPathParseError::new(UnexpectedChar, 0, "/")));
+        assert_eq!(KeyPath::parse_relative("01/a"), Err(KeyPathParseError::new(InvalidIndex, 0, "01")));
+        assert_eq!(KeyPath::parse_relative("1a"), Err(KeyPathParseError::new(UnexpectedChar, 1, "a")));
+        assert_eq!(KeyPath::parse_relative("1#/a"), Err(KeyPathParseError::new(UnexpectedChar, 1, "#")));
+        assert_eq!(KeyPath::parse_relative("1+"), Err(KeyPathParseError::new(EmptySegment, 2, "")));
+        assert_eq!(KeyPath::parse_relative("0/a~2"), Err(KeyPathParseError::new(InvalidEscape, 3, "~2")));
+    }
+
+    #[test]
+    fn relative_key_path_display_round_trips() {
+        for s in ["0", "0#", "1/foo/0", "2-1/a~1b~0c", "0+3#", "0/"] {
+            assert_eq!(relative(s).to_string(), s);
+        }
+    }
+
+    #[test]
+    fn apply_relative_works() {
+        let base = path!["users", 3, "email"];
+        assert_eq!(base.apply_relative(&relative("0")), Ok(base.clone()));
+        assert_eq!(base.apply_relative(&relative("1/name")), Ok(path!["users", 3, "name"]));
+        assert_eq!(base.apply_relative(&relative("3/meta")), Ok(path!["meta"]));
+        assert_eq!(base.apply_relative(&relative("1-2/email")), Ok(path!["users", 1, "email"]));
+    }
+
+    #[test]
+    fn apply_relative_errors() {
+        let base = path!["users", 3, "email"];
+        assert_eq!(base.apply_relative(&relative("4/a")), Err(RelativePathError::AboveRoot { up: 4, len: 3 }));
+        assert_eq!(base.apply_relative(&relative("0+1")), Err(RelativePathError::NotAnIndex));
+        assert_eq!(base.apply_relative(&relative("1-4")), Err(RelativePathError::IndexOutOfRange));
+    }
+
+    #[test]
+    fn relative_key_works() {
+        let base = path!["users", 3, "email"];
+        assert_eq!(base.relative_key(&relative("0#")), Ok(Item::Key("email".into())));
+        assert_eq!(base.relative_key(&relative("1#")), Ok(Item::Index(3)));
+        assert_eq!(base.relative_key(&relative("1+1#")), Ok(Item::Index(4)));
+        assert_eq!(base.relative_key(&relative("3#")), Err(RelativePathError::NoKeyAtRoot));
+    }
+
+    #[test]
+    fn relative_to_works() {
+        let base = path!["config", "servers", 0];
+        assert_eq!(path!["config", "servers", 0, "tls", "cert"].relative_to(&base), Ok(path!["tls", "cert"]));
+        assert_eq!(base.relative_to(&base), Ok(KeyPath::default()));
+        assert_eq!(base.relative_to(&KeyPath::default()), Ok(base.clone()));
+    }
+
+    #[test]
+    fn relative_to_reports_divergence() {
+        let base = path!["config", "servers", 0];
+        let error = path!["config", "clients", 0, "tls"].relative_to(&base).unwrap_err();
+        assert_eq!(error.position(), 1);
+        assert_eq!(error.to_string(), "path diverges from the base at position 1");
+        assert_eq!(path!["config", "servers", "0"].relative_to(&base).unwrap_err().position(), 2);
+    }
+
+    #[test]
+    fn relative_to_errors_for_longer_base() {
+        let base = path!["config", "servers", 0];
+        assert_eq!(path!["config", "servers"].relative_to(&base).unwrap_err().position(), 2);
+    }
+
+    #[test]
+    fn relative_to_ok_works() {
+        assert_eq!(path!["a", "b", "c"].relative_to(&path!["a"]).ok(), Some(path!["b", "c"]));
+        assert_eq!(path!["a", "b", "c"].relative_to(&path!["b"]).ok(), None);
+    }
+}
@@ -0,0 +1,133 @@
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Formatter;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::{Item, KeyPath};
+
+/// `NextIndex` is written `"-"` as in JSON Pointer, and `"-"` reads back as
+/// `NextIndex`. A key that is just `-` therefore doesn't survive a round trip.
+impl Serialize for Item {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        match self {
+            Item::Key(key) => serializer.serialize_str(key),
+            Item::Index(index) => serializer.serialize_u64(*index as u64),
+            Item::NextIndex => serializer.serialize_str("-"),
+        }
+    }
+}
+
+struct ItemVisitor;
+
+impl<'de> Visitor<'de> for ItemVisitor {
+    type Value = Item;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str("a string key or a non-negative integer index")
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> where E: de::Error {
+        usize::try_from(v).map(Item::Index).map_err(|_| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> where E: de::Error {
+        usize::try_from(v).map(Item::Index).map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> where E: de::Error {
+        if v == "-" {
+            return Ok(Item::NextIndex);
+        }
+        Ok(Item::Key(v.to_owned().into()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E> where E: de::Error {
+        if v == "-" {
+            return Ok(Item::NextIndex);
+        }
+        Ok(Item::Key(v.into()))
+    }
+}
+
+impl<'de> Deserialize<'de> for Item {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        deserializer.deserialize_any(ItemVisitor)
+    }
+}
+
+impl Serialize for KeyPath {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for item in self {
+            seq.serialize_element(item)?;
+        }
+        seq.end()
+    }
+}
+
+struct KeyPathVisitor;
+
+impl<'de> Visitor<'de> for KeyPathVisitor {
+    type Value = KeyPath;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str("an array of string keys and integer indices")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error> where A: SeqAccess<'de> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(KeyPath::new(items))
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyPath {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        deserializer.deserialize_seq(KeyPathVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path;
+
+    #[test]
+    fn item_serializes_by_variant() {
+        assert_eq!(serde_json::to_string(&Item::Key("a".into())).unwrap(), "\"a\"");
+        assert_eq!(serde_json::to_string(&Item::Index(5)).unwrap(), "5");
+        assert_eq!(serde_json::to_string(&Item::NextIndex).unwrap(), "\"-\"");
+    }
+
+    #[test]
+    fn key_path_serializes_to_array() {
+        let path = path!["where", "items", 5, "name"];
+        assert_eq!(serde_json::to_string(&path).unwrap(), r#"["where","items",5,"name"]"#);
+    }
+
+    #[test]
+    fn key_path_deserializes_from_array() {
+        let path: KeyPath = serde_json::from_str(r#"["where","items",5,"5"]"#).unwrap();
+        assert_eq!(path, path!["where", "items", 5, "5"]);
+    }
+
+    #[test]
+    fn next_index_round_trips() {
+        let path = path!["items", Item::NextIndex];
+        let json = serde_json::to_string(&path).unwrap();
+        assert_eq!(json, r#"["items","-"]"#);
+        assert_eq!(serde_json::from_str::<KeyPath>(&json).unwrap(), path);
+        assert_eq!(serde_json::from_str::<Item>(r#""-""#).unwrap(), Item::NextIndex);
+    }
+
+    #[test]
+    fn deserialize_rejects_invalid_items() {
+        assert!(serde_json::from_str::<KeyPath>("[-1]").is_err());
+        assert!(serde_json::from_str::<KeyPath>("[1.5]").is_err());
+        assert!(serde_json::from_str::<KeyPath>(r#""a.b""#).is_err());
+    }
+}
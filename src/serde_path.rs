@@ -0,0 +1,95 @@
+use serde_path_to_error::{Path, Segment};
+use crate::{Item, KeyPath};
+
+impl KeyPath {
+
+    /// Converts a `serde_path_to_error` path. Enum variants become keys, and
+    /// segments serde couldn't identify become `unknown`, or are skipped when
+    /// it is `None`.
+    pub fn from_serde_path(path: &Path, unknown: Option<&Item>) -> KeyPath {
+        path.iter().filter_map(|segment| match segment {
+            Segment::Seq { index } => Some(Item::Index(*index)),
+            Segment::Map { key } => Some(Item::from(key)),
+            Segment::Enum { variant } => Some(Item::from(variant)),
+            Segment::Unknown => unknown.cloned(),
+        }).collect()
+    }
+}
+
+/// Skips unknown segments, see `KeyPath::from_serde_path`.
+impl From<&Path> for KeyPath {
+    fn from(path: &Path) -> Self {
+        KeyPath::from_serde_path(path, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use super::*;
+    use crate::path;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Config {
+        server: Server,
+        shape: Shape,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Server {
+        #[serde(rename = "listen.ports")]
+        ports: Vec<u16>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    enum Shape {
+        Circle { radius: f64 },
+    }
+
+    fn error_path(json: &str) -> KeyPath {
+        let deserializer = &mut serde_json::Deserializer::from_str(json);
+        let error = serde_path_to_error::deserialize::<_, Config>(deserializer).unwrap_err();
+        KeyPath::from(error.path())
+    }
+
+    #[test]
+    fn from_serde_path_works() {
+        let path = error_path(r#"{"server":{"listen.ports":[80,"x"]},"shape":{"Circle":{"radius":1}}}"#);
+        assert_eq!(path, path!["server", "listen.ports", 1]);
+        assert_eq!(path.to_string(), r"server.listen\.ports.1");
+    }
+
+    #[test]
+    fn from_serde_path_works_for_enum_variants() {
+        let path = error_path(r#"{"server":{"listen.ports":[80]},"shape":{"Circle":{"radius":"x"}}}"#);
+        assert_eq!(path, path!["shape", "Circle", "radius"]);
+    }
+
+    // A map key that isn't a string or number, here a sequence, is recorded
+    // as `Segment::Unknown`.
+    fn unknown_key_error_path() -> Path {
+        use std::collections::BTreeMap;
+        use serde::de::value::{Error, MapDeserializer};
+        let entries = vec![(vec![1u8], vec!["x"])];
+        let deserializer = MapDeserializer::<_, Error>::new(entries.into_iter());
+        let error = serde_path_to_error::deserialize::<_, BTreeMap<Vec<u8>, Vec<u16>>>(deserializer).unwrap_err();
+        error.path().clone()
+    }
+
+    #[test]
+    fn from_serde_path_replaces_unknown_segments() {
+        let path = unknown_key_error_path();
+        assert!(matches!(path.iter().next(), Some(Segment::Unknown)));
+        assert_eq!(KeyPath::from_serde_path(&path, Some(&Item::from("?"))), path!["?", 0]);
+    }
+
+    #[test]
+    fn from_serde_path_skips_unknown_segments_without_placeholder() {
+        let path = unknown_key_error_path();
+        assert_eq!(KeyPath::from_serde_path(&path, None), path![0]);
+        assert_eq!(KeyPath::from(&path), path![0]);
+    }
+}
@@ -0,0 +1,111 @@
+use alloc::string::{String, ToString};
+use core::fmt::{Display, Formatter};
+use crate::{Item, KeyPath};
+
+/// Error returned by `KeyPath::to_xpath` for an item with no XPath step.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum XPathError {
+    InvalidName { position: usize, key: String },
+    NextIndex { position: usize },
+}
+
+impl Display for XPathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        use XPathError::*;
+        match self {
+            InvalidName { position, key } => write!(f, "key `{}` at position {} is not a valid XML name", key, position),
+            NextIndex { position } => write!(f, "next index at position {} has no XPath step", position),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for XPathError { }
+
+impl KeyPath {
+
+    /// Renders the path as an absolute XPath location path made of child
+    /// steps, like `/order/items[1]/sku`.
+    ///
+    /// Keys become element steps and must be XML names without a namespace
+    /// prefix. An index following a key becomes a 1-based position predicate
+    /// on that step, so `items` then `0` is `items[1]`. Any other index is a
+    /// positional step over all child elements, as in `/*[1]`. The empty path
+    /// is `/`, the document root.
+    pub fn to_xpath(&self) -> Result<String, XPathError> {
+        if self.is_empty() {
+            return Ok("/".to_string());
+        }
+        let mut result = String::new();
+        let mut after_key = false;
+        for (position, item) in self.iter().enumerate() {
+            match item {
+                Item::Key(key) if !is_xml_name(key) => return Err(XPathError::InvalidName { position, key: key.to_string() }),
+                Item::Key(key) => {
+                    result.push('/');
+                    result.push_str(key);
+                }
+                Item::Index(index) => {
+                    if !after_key {
+                        result.push_str("/*");
+                    }
+                    result.push('[');
+                    result.push_str(&(*index as u128 + 1).to_string());
+                    result.push(']');
+                }
+                Item::NextIndex => return Err(XPathError::NextIndex { position }),
+            }
+            after_key = item.is_key();
+        }
+        Ok(result)
+    }
+}
+
+/// Whether `s` matches the `NCName` production of Namespaces in XML, an XML
+/// name without `:`.
+fn is_xml_name(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(is_name_start_char) && chars.all(|c| is_name_start_char(c) || matches!(c, '-' | '.' | '0'..='9' | '\u{B7}' | '\u{300}'..='\u{36F}' | '\u{203F}'..='\u{2040}'))
+}
+
+fn is_name_start_char(c: char) -> bool {
+    matches!(c,
+        'A'..='Z' | '_' | 'a'..='z' | '\u{C0}'..='\u{D6}' | '\u{D8}'..='\u{F6}' | '\u{F8}'..='\u{2FF}'
+        | '\u{370}'..='\u{37D}' | '\u{37F}'..='\u{1FFF}' | '\u{200C}'..='\u{200D}' | '\u{2070}'..='\u{218F}'
+        | '\u{2C00}'..='\u{2FEF}' | '\u{3001}'..='\u{D7FF}' | '\u{F900}'..='\u{FDCF}' | '\u{FDF0}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{EFFFF}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path;
+
+    #[test]
+    fn to_xpath_works() {
+        assert_eq!(path!["order", "items", 0, "sku"].to_xpath(), Ok("/order/items[1]/sku".to_string()));
+        assert_eq!(path!["a-b", "c.d", "_e", "ünï"].to_xpath(), Ok("/a-b/c.d/_e/ünï".to_string()));
+        assert_eq!(KeyPath::default().to_xpath(), Ok("/".to_string()));
+    }
+
+    #[test]
+    fn to_xpath_uses_wildcard_steps_for_indices_without_a_key() {
+        assert_eq!(path![0].to_xpath(), Ok("/*[1]".to_string()));
+        assert_eq!(path!["matrix", 1, 2].to_xpath(), Ok("/matrix[2]/*[3]".to_string()));
+    }
+
+    #[test]
+    fn to_xpath_rejects_invalid_names() {
+        for key in ["", "1a", "-a", "a b", "ns:a", "a/b", "a[1]"] {
+            let path = KeyPath::new(vec![Item::Key("root".into()), Item::Key(key.to_string().into())]);
+            assert_eq!(path.to_xpath(), Err(XPathError::InvalidName { position: 1, key: key.to_string() }));
+        }
+    }
+
+    #[test]
+    fn to_xpath_rejects_next_index() {
+        let path = KeyPath::new(vec![Item::Key("a".into()), Item::NextIndex]);
+        assert_eq!(path.to_xpath(), Err(XPathError::NextIndex { position: 1 }));
+        assert_eq!(path.to_xpath().unwrap_err().to_string(), "next index at position 1 has no XPath step");
+    }
+}